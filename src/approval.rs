@@ -0,0 +1,215 @@
+//! Approvals allowing the vault relayer to transfer the sell tokens of orders.
+
+use alloy::{
+    primitives::{Address, TxHash, U256},
+    providers::Provider,
+    rpc::types::TransactionRequest,
+    sol_types::SolCall,
+};
+use eyre::{Result, WrapErr};
+use tracing::info;
+
+use crate::{
+    config::Network,
+    contracts::{IBalancerVault, IERC20},
+    primitives::order_data::{OrderData, SellTokenSource},
+};
+
+/// Amount to approve.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ApprovalAmount {
+    Exact(U256),
+    Unlimited,
+}
+
+impl ApprovalAmount {
+    pub fn value(&self) -> U256 {
+        match self {
+            ApprovalAmount::Exact(amount) => *amount,
+            ApprovalAmount::Unlimited => U256::MAX,
+        }
+    }
+}
+
+/// Transaction approving the vault relayer of `network` to spend `token`,
+/// needed for orders selling from ERC-20 balances.
+pub fn approve_vault_relayer_tx(
+    network: &Network,
+    token: Address,
+    amount: ApprovalAmount,
+) -> TransactionRequest {
+    approve_tx(token, network.vault_relayer(), amount)
+}
+
+/// Transaction approving the Balancer vault of `network` to spend `token`,
+/// needed for orders selling from external balances.
+pub fn approve_balancer_vault_tx(
+    network: &Network,
+    token: Address,
+    amount: ApprovalAmount,
+) -> TransactionRequest {
+    approve_tx(token, network.balancer_vault(), amount)
+}
+
+/// Transaction approving, or revoking, the vault relayer as a Balancer vault
+/// relayer of `owner`, needed for orders selling from external or internal
+/// balances. Must be sent by `owner`.
+pub fn set_balancer_relayer_approval_tx(
+    network: &Network,
+    owner: Address,
+    approved: bool,
+) -> TransactionRequest {
+    let call = IBalancerVault::setRelayerApprovalCall {
+        sender: owner,
+        relayer: network.vault_relayer(),
+        approved,
+    };
+    TransactionRequest::default()
+        .from(owner)
+        .to(network.balancer_vault())
+        .input(call.abi_encode().into())
+}
+
+fn approve_tx(token: Address, spender: Address, amount: ApprovalAmount) -> TransactionRequest {
+    let call = IERC20::approveCall { spender, amount: amount.value() };
+    TransactionRequest::default().to(token).input(call.abi_encode().into())
+}
+
+/// Reads and sends the approvals needed to trade on a network.
+#[derive(Debug)]
+pub struct TokenApprovals<P> {
+    provider: P,
+    network: Network,
+}
+
+impl<P: Provider> TokenApprovals<P> {
+    pub fn new(provider: P, network: Network) -> Self {
+        Self { provider, network }
+    }
+
+    /// Current allowance of the vault relayer for `owner`'s `token`.
+    pub async fn allowance(&self, token: Address, owner: Address) -> Result<U256> {
+        Ok(IERC20::new(token, &self.provider)
+            .allowance(owner, self.network.vault_relayer())
+            .call()
+            .await
+            .wrap_err_with(|| format!("Failed to get allowance of {} for {}", owner, token))?
+            ._0)
+    }
+
+    /// Current allowance of the Balancer vault for `owner`'s `token`.
+    pub async fn balancer_vault_allowance(&self, token: Address, owner: Address) -> Result<U256> {
+        Ok(IERC20::new(token, &self.provider)
+            .allowance(owner, self.network.balancer_vault())
+            .call()
+            .await
+            .wrap_err_with(|| format!("Failed to get allowance of {} for {}", owner, token))?
+            ._0)
+    }
+
+    /// Whether `owner` approved the vault relayer as a Balancer vault relayer.
+    pub async fn has_approved_balancer_relayer(&self, owner: Address) -> Result<bool> {
+        Ok(IBalancerVault::new(self.network.balancer_vault(), &self.provider)
+            .hasApprovedRelayer(owner, self.network.vault_relayer())
+            .call()
+            .await
+            .wrap_err("Failed to get Balancer relayer approval")?
+            ._0)
+    }
+
+    /// Transactions still needed before `owner` can place `order`, depending
+    /// on where the sell tokens are taken from.
+    pub async fn missing_approvals(
+        &self,
+        order: &OrderData,
+        owner: Address,
+    ) -> Result<Vec<TransactionRequest>> {
+        // An overflowing order needs every token, i.e. an unlimited approval
+        let required = order.sell_amount.saturating_add(order.fee_amount);
+        let amount = ApprovalAmount::Exact(required);
+        let mut transactions = Vec::new();
+
+        match order.sell_token_balance {
+            SellTokenSource::Erc20 =>
+                if self.allowance(order.sell_token, owner).await? < required {
+                    transactions.push(approve_vault_relayer_tx(
+                        &self.network,
+                        order.sell_token,
+                        amount,
+                    ));
+                },
+            SellTokenSource::External => {
+                if self.balancer_vault_allowance(order.sell_token, owner).await? < required {
+                    transactions.push(approve_balancer_vault_tx(
+                        &self.network,
+                        order.sell_token,
+                        amount,
+                    ));
+                }
+                if !self.has_approved_balancer_relayer(owner).await? {
+                    transactions.push(set_balancer_relayer_approval_tx(&self.network, owner, true));
+                }
+            }
+            SellTokenSource::Internal =>
+                if !self.has_approved_balancer_relayer(owner).await? {
+                    transactions.push(set_balancer_relayer_approval_tx(&self.network, owner, true));
+                },
+        }
+
+        Ok(transactions)
+    }
+
+    /// Sends a transaction with the provider's wallet and waits for it to be
+    /// included.
+    pub async fn send(&self, tx: TransactionRequest) -> Result<TxHash> {
+        let pending =
+            self.provider.send_transaction(tx).await.wrap_err("Failed to send transaction")?;
+        let tx_hash = pending.watch().await.wrap_err("Failed to confirm transaction")?;
+        info!("Approval transaction confirmed: {}", tx_hash);
+        Ok(tx_hash)
+    }
+
+    /// Approves the vault relayer to spend `token`.
+    pub async fn approve(&self, token: Address, amount: ApprovalAmount) -> Result<TxHash> {
+        self.send(approve_vault_relayer_tx(&self.network, token, amount)).await
+    }
+
+    /// Approves the vault relayer as a Balancer vault relayer of `owner`.
+    pub async fn approve_balancer_relayer(&self, owner: Address) -> Result<TxHash> {
+        self.send(set_balancer_relayer_approval_tx(&self.network, owner, true)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::address;
+
+    use super::*;
+
+    const DAI: Address = address!("0x6B175474E89094C44Da98b954EedeAC495271d0F");
+
+    #[test]
+    fn test_unlimited_approval_of_vault_relayer() {
+        let tx = approve_vault_relayer_tx(&Network::Mainnet, DAI, ApprovalAmount::Unlimited);
+        let call = IERC20::approveCall::abi_decode(tx.input.input().unwrap(), true).unwrap();
+
+        assert_eq!(tx.to, Some(DAI.into()));
+        assert_eq!(call.spender, Network::Mainnet.vault_relayer());
+        assert_eq!(call.amount, U256::MAX);
+    }
+
+    #[test]
+    fn test_balancer_relayer_approval() {
+        let owner = Address::repeat_byte(1);
+
+        let tx = set_balancer_relayer_approval_tx(&Network::Gnosis, owner, true);
+        let call =
+            IBalancerVault::setRelayerApprovalCall::abi_decode(tx.input.input().unwrap(), true)
+                .unwrap();
+
+        assert_eq!(tx.to, Some(Network::Gnosis.balancer_vault().into()));
+        assert_eq!(call.sender, owner);
+        assert_eq!(call.relayer, Network::Gnosis.vault_relayer());
+        assert!(call.approved);
+    }
+}
@@ -0,0 +1,381 @@
+//! Command-line access to the CoW Protocol orderbook.
+
+mod output;
+mod signing;
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use alloy::primitives::{Address, B256, Bytes, TxHash};
+use clap::{Parser, Subcommand};
+use cow_sdk::{
+    config::Network,
+    models::{
+        order::{Order, OrderCancellations, OrderCreation, PartialOrder},
+        response::SolverCompetitionResponse,
+        trade::Trade,
+    },
+    orderbook::{GetTradesQuery, OrderApiClient},
+    primitives::{
+        app_data::{AppData, AppDataHash},
+        order_uid::OrderUid,
+    },
+};
+use eyre::{Result, WrapErr, eyre};
+use output::{OutputFormat, Table, print};
+use serde::Serialize;
+use signing::{SignerArgs, read_app_data, read_order};
+use tracing_subscriber::EnvFilter;
+
+#[derive(Debug, Parser)]
+#[command(name = "cow", version, about = "Query and use the CoW Protocol orderbook")]
+struct Cli {
+    /// Network to use, e.g. `mainnet`, `gnosis` or `base-staging`.
+    #[arg(long, short, global = true, default_value = "mainnet")]
+    network: Network,
+
+    /// Output format.
+    #[arg(long, short, global = true, value_enum, default_value_t)]
+    output: OutputFormat,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Get an order or its status.
+    #[command(subcommand)]
+    Order(OrderCommand),
+    /// List the orders of an owner.
+    Orders {
+        owner: Address,
+        #[arg(long)]
+        offset: Option<u32>,
+        #[arg(long)]
+        limit: Option<u32>,
+    },
+    /// List trades by owner or order.
+    Trades {
+        #[arg(long, conflicts_with = "order", required_unless_present = "order")]
+        owner: Option<Address>,
+        #[arg(long)]
+        order: Option<OrderUid>,
+    },
+    /// Get a quote for the request in a JSON file.
+    Quote { request: PathBuf },
+    /// Get a solver competition: `latest`, an auction ID, or `tx <hash>`.
+    Competition { target: String, tx_hash: Option<TxHash> },
+    /// Get or upload app data.
+    #[command(subcommand)]
+    AppData(AppDataCommand),
+    /// Get the native price of a token.
+    Price { token: Address },
+    /// Get the orderbook API version.
+    Version,
+}
+
+#[derive(Debug, Subcommand)]
+enum OrderCommand {
+    /// Get an order by UID.
+    Get { uid: OrderUid },
+    /// Get the competition status of an order.
+    Status { uid: OrderUid },
+    /// Sign an order described in a JSON or TOML file, optionally posting it.
+    Sign {
+        file: PathBuf,
+        #[command(flatten)]
+        signer: SignerArgs,
+        /// Full app data JSON document the order signs the hash of.
+        #[arg(long)]
+        app_data: Option<PathBuf>,
+        /// Post the signed order to the orderbook.
+        #[arg(long)]
+        post: bool,
+    },
+    /// Cancel orders off-chain.
+    Cancel {
+        #[arg(required = true)]
+        uids: Vec<OrderUid>,
+        #[command(flatten)]
+        signer: SignerArgs,
+    },
+}
+
+/// Output of `order sign`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SignedOrder {
+    owner: Address,
+    digest: B256,
+    uid: OrderUid,
+    signature: Bytes,
+    posted: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum AppDataCommand {
+    /// Get the full app data of a hash.
+    Get { hash: AppDataHash },
+    /// Upload app data from a JSON file.
+    Upload {
+        file: PathBuf,
+        /// Upload under this hash instead of letting the API compute it.
+        #[arg(long)]
+        hash: Option<AppDataHash>,
+    },
+}
+
+/// Solver competition selected by the `competition` command.
+#[derive(Debug, PartialEq, Eq)]
+enum CompetitionTarget {
+    Latest,
+    Id(i64),
+    TxHash(TxHash),
+}
+
+impl CompetitionTarget {
+    fn parse(target: &str, tx_hash: Option<TxHash>) -> Result<Self> {
+        match (target, tx_hash) {
+            ("latest", None) => Ok(Self::Latest),
+            ("tx", Some(tx_hash)) => Ok(Self::TxHash(tx_hash)),
+            ("tx", None) => Err(eyre!("Missing transaction hash after `tx`")),
+            (id, None) => Ok(Self::Id(id.parse().wrap_err_with(|| {
+                format!("Expected `latest`, an auction ID or `tx <hash>`, got `{}`", id)
+            })?)),
+            (target, Some(_)) => Err(eyre!("Unexpected transaction hash after `{}`", target)),
+        }
+    }
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T> {
+    let contents =
+        fs::read_to_string(path).wrap_err_with(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&contents).wrap_err_with(|| format!("Failed to parse {}", path.display()))
+}
+
+fn order_table(order: &Order) -> Table {
+    Table::fields()
+        .row([&"uid", &order.uid])
+        .row([&"owner", &order.owner])
+        .row([&"status", &format!("{:?}", order.status)])
+        .row([&"kind", &order.kind])
+        .row([&"sell token", &order.sell_token])
+        .row([&"buy token", &order.buy_token])
+        .row([&"sell amount", &order.sell_amount])
+        .row([&"buy amount", &order.buy_amount])
+        .row([&"executed sell amount", &order.executed_sell_amount])
+        .row([&"executed buy amount", &order.executed_buy_amount])
+        .row([&"valid to", &order.valid_to])
+        .row([&"created", &order.creation_date])
+}
+
+fn orders_table(orders: &[Order]) -> Table {
+    orders.iter().fold(
+        Table::new(["UID", "STATUS", "KIND", "SELL AMOUNT", "BUY AMOUNT", "CREATED"]),
+        |table, order| {
+            table.row([
+                &order.uid,
+                &format!("{:?}", order.status),
+                &order.kind,
+                &order.sell_amount,
+                &order.buy_amount,
+                &order.creation_date,
+            ])
+        },
+    )
+}
+
+fn trades_table(trades: &[Trade]) -> Table {
+    trades.iter().fold(
+        Table::new(["BLOCK", "ORDER", "SELL AMOUNT", "BUY AMOUNT", "TX HASH"]),
+        |table, trade| {
+            table.row([
+                &trade.block_number,
+                &trade.order_uid,
+                &trade.sell_amount,
+                &trade.buy_amount,
+                &trade.tx_hash,
+            ])
+        },
+    )
+}
+
+fn competition_table(competition: &SolverCompetitionResponse) -> Table {
+    let tx_hashes = competition
+        .transaction_hashes
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    Table::fields()
+        .row([&"auction id", &competition.auction_id])
+        .row([&"auction start block", &competition.auction_start_block])
+        .row([&"simulation block", &competition.competition_simulation_block])
+        .row([&"transactions", &tx_hashes])
+        .row([&"solutions", &competition.solutions.as_ref().map_or(0, Vec::len)])
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let client = OrderApiClient::new(cli.network)?;
+    let format = cli.output;
+
+    match cli.command {
+        Command::Order(OrderCommand::Get { uid }) =>
+            print(format, &client.get_order_by_id(&uid).await?, order_table),
+        Command::Order(OrderCommand::Status { uid }) =>
+            print(format, &client.get_order_status(&uid).await?, |status| {
+                status.value.iter().fold(
+                    Table::fields().row([&"status", &format!("{:?}", status.r#type)]),
+                    |table, inclusion| table.row([&"solver", &inclusion.solver]),
+                )
+            }),
+        Command::Order(OrderCommand::Sign { file, signer, app_data, post }) => {
+            let signer = signer.signer()?;
+            let domain = cli.network.settlement_domain();
+            let mut order = read_order(&file)?;
+            let full_app_data =
+                app_data.map(|path| read_app_data(&path, &mut order)).transpose()?;
+
+            let signature = order.sign(&domain, &signer).await?;
+            let mut creation = OrderCreation::new(&order, &signature, signer.address());
+            if let Some(full_app_data) = full_app_data {
+                creation = creation.full_app_data(full_app_data);
+            }
+            let uid = if post {
+                client.post_order(&creation).await?
+            } else {
+                order.uid(&domain, signer.address())
+            };
+
+            let signed = SignedOrder {
+                owner: signer.address(),
+                digest: order.digest(&domain),
+                uid,
+                signature: creation.signature,
+                posted: post,
+            };
+            print(format, &signed, |signed| {
+                Table::fields()
+                    .row([&"owner", &signed.owner])
+                    .row([&"digest", &signed.digest])
+                    .row([&"uid", &signed.uid])
+                    .row([&"signature", &signed.signature])
+                    .row([&"posted", &signed.posted])
+            })
+        }
+        Command::Order(OrderCommand::Cancel { uids, signer }) => {
+            let signer = signer.signer()?;
+            let cancellations =
+                OrderCancellations::sign(uids, &cli.network.settlement_domain(), &signer).await?;
+            client.cancel_order(&cancellations).await?;
+            print(format, &cancellations.order_ids, |uids| {
+                uids.iter().fold(Table::new(["CANCELLED"]), |table, uid| table.row([uid]))
+            })
+        }
+        Command::Orders { owner, offset, limit } =>
+            print(format, &client.get_user_orders(&owner, offset, limit).await?, |orders| {
+                orders_table(orders)
+            }),
+        Command::Trades { owner, order } => {
+            let query = match (owner, order) {
+                (Some(owner), _) => GetTradesQuery::ByOwner(owner),
+                (None, Some(order)) => GetTradesQuery::ByOrderId(order),
+                (None, None) => return Err(eyre!("Either --owner or --order is required")),
+            };
+            print(format, &client.get_trades(&query).await?, |trades| trades_table(trades))
+        }
+        Command::Quote { request } => {
+            let request: PartialOrder = read_json(&request)?;
+            print(format, &client.get_quote(&request).await?, |response| {
+                let quote = &response.quote;
+                Table::fields()
+                    .row([&"id", &response.id])
+                    .row([&"sell token", &quote.sell_token])
+                    .row([&"buy token", &quote.buy_token])
+                    .row([&"sell amount", &quote.sell_amount])
+                    .row([&"buy amount", &quote.buy_amount])
+                    .row([&"fee amount", &quote.fee_amount])
+                    .row([&"kind", &quote.kind])
+                    .row([&"valid to", &quote.valid_to])
+                    .row([&"expiration", &response.expiration])
+                    .row([&"verified", &response.verified])
+            })
+        }
+        Command::Competition { target, tx_hash } => {
+            let competition = match CompetitionTarget::parse(&target, tx_hash)? {
+                CompetitionTarget::Latest => client.get_latest_competition().await?,
+                CompetitionTarget::Id(id) => client.get_competition_by_id(&id).await?,
+                CompetitionTarget::TxHash(tx_hash) =>
+                    client.get_competition_by_tx_hash(&tx_hash).await?,
+            };
+            print(format, &competition, competition_table)
+        }
+        Command::AppData(AppDataCommand::Get { hash }) =>
+            print(format, &client.get_app_data(&hash).await?, |app_data| {
+                Table::fields().row([&"full app data", &app_data.full_app_data])
+            }),
+        Command::AppData(AppDataCommand::Upload { file, hash }) => {
+            let app_data: AppData = read_json(&file)?;
+            let hash = match hash {
+                Some(hash) => client.upload_app_data_by_hash(&hash, &app_data).await?,
+                None => client.upload_app_data(&app_data).await?,
+            };
+            print(format, &hash, |hash| Table::fields().row([&"app data hash", hash]))
+        }
+        Command::Price { token } =>
+            print(format, &client.get_token_price(&token).await?, |price| {
+                Table::fields().row([&"token", &token]).row([&"native price", &price.price])
+            }),
+        Command::Version => print(format, &client.get_version().await?, |version| {
+            Table::fields().row([&"version", version])
+        }),
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(io::stderr)
+        .without_time()
+        .init();
+    run(Cli::parse()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_competition_target() {
+        let tx_hash = TxHash::repeat_byte(1);
+
+        assert_eq!(CompetitionTarget::parse("latest", None).unwrap(), CompetitionTarget::Latest);
+        assert_eq!(CompetitionTarget::parse("42", None).unwrap(), CompetitionTarget::Id(42));
+        assert_eq!(
+            CompetitionTarget::parse("tx", Some(tx_hash)).unwrap(),
+            CompetitionTarget::TxHash(tx_hash)
+        );
+        assert!(CompetitionTarget::parse("tx", None).is_err());
+        assert!(CompetitionTarget::parse("latest", Some(tx_hash)).is_err());
+    }
+
+    #[test]
+    fn test_cli_parses_network() {
+        let cli = Cli::try_parse_from([
+            "cow",
+            "--network",
+            "gnosis",
+            "order",
+            "status",
+            &format!("0x{}", "11".repeat(56)),
+        ])
+        .unwrap();
+
+        assert_eq!(cli.network, Network::Gnosis);
+        assert!(matches!(cli.command, Command::Order(OrderCommand::Status { .. })));
+    }
+}
@@ -0,0 +1,97 @@
+use std::fmt::Display;
+
+use clap::ValueEnum;
+use eyre::{Result, WrapErr};
+use serde::Serialize;
+
+/// How results are printed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+}
+
+/// Plain-text table with left-aligned columns.
+#[derive(Debug, Default)]
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new<const N: usize>(headers: [&str; N]) -> Self {
+        Self {
+            headers: headers.iter().map(|header| header.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Two-column table of fields and their values.
+    pub fn fields() -> Self {
+        Self::new(["FIELD", "VALUE"])
+    }
+
+    pub fn row<const N: usize>(mut self, cells: [&dyn Display; N]) -> Self {
+        self.rows.push(cells.iter().map(|cell| cell.to_string()).collect());
+        self
+    }
+
+    pub fn render(&self) -> String {
+        let widths: Vec<usize> = (0..self.headers.len())
+            .map(|column| {
+                self.rows
+                    .iter()
+                    .filter_map(|row| row.get(column))
+                    .chain([&self.headers[column]])
+                    .map(|cell| cell.chars().count())
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        [&self.headers]
+            .into_iter()
+            .chain(&self.rows)
+            .map(|row| {
+                row.iter()
+                    .zip(&widths)
+                    .map(|(cell, width)| format!("{:width$}", cell, width = width))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+                    .trim_end()
+                    .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Prints `value` as JSON, or as the table built by `table` otherwise.
+pub fn print<T: Serialize>(
+    format: OutputFormat,
+    value: &T,
+    table: impl FnOnce(&T) -> Table,
+) -> Result<()> {
+    match format {
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(value).wrap_err("Failed to serialize output")?
+        ),
+        OutputFormat::Table => println!("{}", table(value).render()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_aligns_columns() {
+        let table =
+            Table::new(["UID", "STATUS"]).row([&"0x01", &"open"]).row([&"0x0203", &"fulfilled"]);
+
+        assert_eq!(table.render(), "UID     STATUS\n0x01    open\n0x0203  fulfilled");
+    }
+}
@@ -0,0 +1,116 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use alloy::{primitives::keccak256, signers::local::PrivateKeySigner};
+use clap::Args;
+use cow_sdk::primitives::{app_data::AppDataHash, order_data::OrderData};
+use eyre::{Result, WrapErr, eyre};
+
+/// Key of the order owner.
+#[derive(Debug, Args)]
+pub struct SignerArgs {
+    /// Hex-encoded private key.
+    #[arg(
+        long,
+        env = "COW_PRIVATE_KEY",
+        hide_env_values = true,
+        conflicts_with = "keystore",
+        required_unless_present = "keystore"
+    )]
+    private_key: Option<String>,
+
+    /// Encrypted JSON keystore file.
+    #[arg(long)]
+    keystore: Option<PathBuf>,
+
+    /// Password of the keystore file.
+    #[arg(long, env = "COW_KEYSTORE_PASSWORD", hide_env_values = true, requires = "keystore")]
+    password: Option<String>,
+}
+
+impl SignerArgs {
+    pub fn signer(&self) -> Result<PrivateKeySigner> {
+        match (&self.private_key, &self.keystore) {
+            (Some(private_key), _) => private_key.trim().parse().wrap_err("Invalid private key"),
+            (None, Some(keystore)) => {
+                let password = self.password.as_ref().ok_or_else(|| {
+                    eyre!("Keystore password required via --password or COW_KEYSTORE_PASSWORD")
+                })?;
+                PrivateKeySigner::decrypt_keystore(keystore, password)
+                    .wrap_err_with(|| format!("Failed to decrypt {}", keystore.display()))
+            }
+            (None, None) => Err(eyre!("Either --private-key or --keystore is required")),
+        }
+    }
+}
+
+/// Reads an order from a TOML file, or a JSON file for any other extension.
+pub fn read_order(path: &Path) -> Result<OrderData> {
+    let contents =
+        fs::read_to_string(path).wrap_err_with(|| format!("Failed to read {}", path.display()))?;
+    parse_order(&contents, path.extension().is_some_and(|extension| extension == "toml"))
+        .wrap_err_with(|| format!("Failed to parse order in {}", path.display()))
+}
+
+fn parse_order(contents: &str, toml: bool) -> Result<OrderData> {
+    if toml { Ok(toml::from_str(contents)?) } else { Ok(serde_json::from_str(contents)?) }
+}
+
+/// Reads a full app data document, setting its hash on `order` when the order
+/// has none and checking it otherwise.
+pub fn read_app_data(path: &Path, order: &mut OrderData) -> Result<String> {
+    let app_data = fs::read_to_string(path)
+        .wrap_err_with(|| format!("Failed to read {}", path.display()))?
+        .trim_end()
+        .to_string();
+    let hash = AppDataHash(keccak256(&app_data).0);
+    if order.app_data.is_zero() {
+        order.app_data = hash;
+    } else if order.app_data != hash {
+        return Err(eyre!(
+            "App data in {} hashes to {}, but the order signs {}",
+            path.display(),
+            hash,
+            order.app_data
+        ));
+    }
+    Ok(app_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::U256;
+
+    use super::*;
+
+    #[test]
+    fn test_json_and_toml_orders_match() {
+        let json = r#"{
+            "sellToken": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+            "buyToken": "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+            "sellAmount": "1000000000",
+            "buyAmount": "500000000000000000",
+            "validTo": 1700000000,
+            "appData": "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "kind": "sell",
+            "partiallyFillable": false
+        }"#;
+        let toml = r#"
+            sellToken = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"
+            buyToken = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"
+            sellAmount = "1000000000"
+            buyAmount = "500000000000000000"
+            validTo = 1700000000
+            appData = "0x0000000000000000000000000000000000000000000000000000000000000000"
+            kind = "sell"
+            partiallyFillable = false
+        "#;
+
+        let order = parse_order(json, false).unwrap();
+
+        assert_eq!(order, parse_order(toml, true).unwrap());
+        assert_eq!(order.sell_amount, U256::from(1_000_000_000));
+    }
+}
@@ -0,0 +1,104 @@
+//! Block timestamps, for annotating trades with the time they were settled.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Mutex, PoisonError},
+};
+
+use alloy::{
+    eips::BlockNumberOrTag,
+    providers::Provider,
+    rpc::{client::BatchRequest, types::Block},
+};
+use chrono::{DateTime, Utc};
+use eyre::{Result, WrapErr, eyre};
+use tracing::debug;
+
+use crate::models::trade::{TimedTrade, Trade};
+
+/// Most blocks requested in one JSON-RPC batch.
+pub const MAX_BATCH_SIZE: usize = 100;
+
+/// Resolves and caches the timestamps of blocks.
+#[derive(Debug)]
+pub struct BlockTimestamps<P> {
+    provider: P,
+    timestamps: Mutex<HashMap<u64, DateTime<Utc>>>,
+}
+
+impl<P: Provider> BlockTimestamps<P> {
+    pub fn new(provider: P) -> Self {
+        Self { provider, timestamps: Mutex::default() }
+    }
+
+    /// Timestamp of a block.
+    pub async fn timestamp(&self, block_number: u64) -> Result<DateTime<Utc>> {
+        let timestamps = self.timestamps([block_number]).await?;
+        Ok(timestamps[&block_number])
+    }
+
+    /// Timestamps of several blocks. Uncached blocks are requested in batches
+    /// of at most [`MAX_BATCH_SIZE`].
+    pub async fn timestamps(
+        &self,
+        block_numbers: impl IntoIterator<Item = u64>,
+    ) -> Result<HashMap<u64, DateTime<Utc>>> {
+        let block_numbers: BTreeSet<u64> = block_numbers.into_iter().collect();
+        let missing: Vec<u64> = {
+            let cache = self.timestamps.lock().unwrap_or_else(PoisonError::into_inner);
+            block_numbers.iter().copied().filter(|block| !cache.contains_key(block)).collect()
+        };
+
+        for batch in missing.chunks(MAX_BATCH_SIZE) {
+            let fetched = self.fetch(batch).await?;
+            self.timestamps.lock().unwrap_or_else(PoisonError::into_inner).extend(fetched);
+        }
+
+        let cache = self.timestamps.lock().unwrap_or_else(PoisonError::into_inner);
+        Ok(block_numbers.into_iter().map(|block| (block, cache[&block])).collect())
+    }
+
+    /// Annotates trades with the timestamps of their blocks.
+    pub async fn annotate(&self, trades: Vec<Trade>) -> Result<Vec<TimedTrade>> {
+        let timestamps = self.timestamps(trades.iter().map(|trade| trade.block_number)).await?;
+        Ok(trades
+            .into_iter()
+            .map(|trade| TimedTrade { timestamp: timestamps[&trade.block_number], trade })
+            .collect())
+    }
+
+    /// Requests the blocks in one batch.
+    async fn fetch(&self, block_numbers: &[u64]) -> Result<Vec<(u64, DateTime<Utc>)>> {
+        debug!("Requesting timestamps of {} blocks", block_numbers.len());
+        let client = self.provider.client();
+        let mut batch = BatchRequest::new(client);
+        let waiters = block_numbers
+            .iter()
+            .map(|&block| {
+                batch
+                    .add_call::<_, Option<Block>>(
+                        "eth_getBlockByNumber",
+                        &(BlockNumberOrTag::Number(block), false),
+                    )
+                    .map(|waiter| (block, waiter))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .wrap_err("Failed to build block request batch")?;
+        batch.send().await.wrap_err("Failed to send block request batch")?;
+
+        let mut timestamps = Vec::with_capacity(waiters.len());
+        for (block, waiter) in waiters {
+            let header = waiter
+                .await
+                .wrap_err_with(|| format!("Failed to get block {}", block))?
+                .ok_or_else(|| eyre!("Block {} not found", block))?
+                .header;
+            let timestamp = i64::try_from(header.timestamp)
+                .ok()
+                .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+                .ok_or_else(|| eyre!("Invalid timestamp of block {}", block))?;
+            timestamps.push((block, timestamp));
+        }
+        Ok(timestamps)
+    }
+}
@@ -0,0 +1,201 @@
+use alloy::{
+    primitives::{B256, Bytes, U256, keccak256},
+    sol_types::{SolCall, SolValue},
+};
+use eyre::{Result, eyre};
+
+use crate::conditional::{ComposableCoW, ConditionalOrderParams, IComposableCoW};
+
+/// Where watch-towers can find the proofs for the orders of a merkle root.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum ProofLocation {
+    /// Proofs are kept by the owner and not published.
+    #[default]
+    Private,
+    /// Proofs and parameters are emitted on-chain in the `MerkleRootSet` event.
+    Emitted,
+}
+
+impl ProofLocation {
+    const fn as_u8(&self) -> u8 {
+        match self {
+            ProofLocation::Private => 0,
+            ProofLocation::Emitted => 1,
+        }
+    }
+}
+
+/// Merkle tree over a set of conditional orders, compatible with OpenZeppelin's
+/// `StandardMerkleTree` as verified by ComposableCoW.
+#[derive(Debug, Clone)]
+pub struct ConditionalOrderTree {
+    orders: Vec<ConditionalOrderParams>,
+    /// Flattened tree, the root is at index 0 and the leaves at the end.
+    tree: Vec<B256>,
+    /// Index in `tree` of the leaf of each order in `orders`.
+    leaf_indices: Vec<usize>,
+}
+
+impl ConditionalOrderTree {
+    pub fn new(orders: Vec<ConditionalOrderParams>) -> Result<Self> {
+        if orders.is_empty() {
+            return Err(eyre!("Cannot build a merkle tree without orders"));
+        }
+
+        // Leaves are sorted by hash, as done by `StandardMerkleTree.of`
+        let mut leaves: Vec<(usize, B256)> =
+            orders.iter().map(leaf_hash).enumerate().collect::<Vec<_>>();
+        leaves.sort_by_key(|(_, hash)| *hash);
+
+        let tree_len = 2 * leaves.len() - 1;
+        let mut tree = vec![B256::ZERO; tree_len];
+        let mut leaf_indices = vec![0; orders.len()];
+        for (position, (order_index, hash)) in leaves.into_iter().enumerate() {
+            let tree_index = tree_len - 1 - position;
+            tree[tree_index] = hash;
+            leaf_indices[order_index] = tree_index;
+        }
+        for index in (0..tree_len - orders.len()).rev() {
+            tree[index] = hash_pair(tree[2 * index + 1], tree[2 * index + 2]);
+        }
+
+        Ok(Self { orders, tree, leaf_indices })
+    }
+
+    /// Merkle root to pass to `ComposableCoW.setRoot`.
+    pub fn root(&self) -> B256 {
+        self.tree[0]
+    }
+
+    pub fn orders(&self) -> &[ConditionalOrderParams] {
+        &self.orders
+    }
+
+    /// Merkle proof for an order of the tree, `None` if it is not part of it.
+    pub fn proof(&self, order: &ConditionalOrderParams) -> Option<Vec<B256>> {
+        let position = self.orders.iter().position(|candidate| candidate == order)?;
+
+        let mut index = self.leaf_indices[position];
+        let mut proof = Vec::new();
+        while index > 0 {
+            let sibling = if index % 2 == 1 { index + 1 } else { index - 1 };
+            proof.push(self.tree[sibling]);
+            index = (index - 1) / 2;
+        }
+        Some(proof)
+    }
+
+    /// Calldata for `ComposableCoW.setRoot`, replacing all merkle authorised
+    /// orders of the owner with the orders of this tree.
+    pub fn set_root_calldata(&self, location: ProofLocation) -> Bytes {
+        IComposableCoW::setRootCall { root: self.root(), proof: self.proof_struct(location) }
+            .abi_encode()
+            .into()
+    }
+
+    fn proof_struct(&self, location: ProofLocation) -> ComposableCoW::Proof {
+        let data = match location {
+            ProofLocation::Private => Bytes::new(),
+            ProofLocation::Emitted => self
+                .orders
+                .iter()
+                .map(|order| ComposableCoW::ProofWithParams {
+                    proof: self.proof(order).unwrap_or_default(),
+                    params: order.to_sol(),
+                })
+                .collect::<Vec<_>>()
+                .abi_encode()
+                .into(),
+        };
+        ComposableCoW::Proof { location: U256::from(location.as_u8()), data }
+    }
+}
+
+/// Leaf of an order, `keccak256(bytes.concat(keccak256(abi.encode(params))))`.
+fn leaf_hash(order: &ConditionalOrderParams) -> B256 {
+    keccak256(order.id())
+}
+
+fn hash_pair(a: B256, b: B256) -> B256 {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    keccak256([first.as_slice(), second.as_slice()].concat())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::Address;
+
+    use super::*;
+
+    fn orders(count: u8) -> Vec<ConditionalOrderParams> {
+        (0..count)
+            .map(|i| ConditionalOrderParams::new(Address::ZERO, B256::repeat_byte(i), Bytes::new()))
+            .collect()
+    }
+
+    /// Mirrors `MerkleProof.verify` from OpenZeppelin.
+    fn verify(proof: &[B256], root: B256, leaf: B256) -> bool {
+        proof.iter().fold(leaf, |hash, sibling| hash_pair(hash, *sibling)) == root
+    }
+
+    #[test]
+    fn test_tree_requires_orders() {
+        assert!(ConditionalOrderTree::new(vec![]).is_err());
+    }
+
+    #[test]
+    fn test_single_order_root_is_its_leaf() {
+        let orders = orders(1);
+        let tree = ConditionalOrderTree::new(orders.clone()).unwrap();
+
+        assert_eq!(tree.root(), leaf_hash(&orders[0]));
+        assert_eq!(tree.proof(&orders[0]), Some(vec![]));
+    }
+
+    #[test]
+    fn test_proofs_verify_against_root() {
+        for count in 2..=7 {
+            let orders = orders(count);
+            let tree = ConditionalOrderTree::new(orders.clone()).unwrap();
+
+            for order in &orders {
+                let proof = tree.proof(order).unwrap();
+                assert!(verify(&proof, tree.root(), leaf_hash(order)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_root_does_not_depend_on_order() {
+        let orders = orders(5);
+        let reversed = orders.iter().rev().cloned().collect();
+
+        assert_eq!(
+            ConditionalOrderTree::new(orders).unwrap().root(),
+            ConditionalOrderTree::new(reversed).unwrap().root()
+        );
+    }
+
+    #[test]
+    fn test_proof_for_unknown_order_is_none() {
+        let tree = ConditionalOrderTree::new(orders(3)).unwrap();
+        let unknown =
+            ConditionalOrderParams::new(Address::ZERO, B256::repeat_byte(9), Bytes::new());
+
+        assert!(tree.proof(&unknown).is_none());
+    }
+
+    #[test]
+    fn test_set_root_calldata_emits_proofs() {
+        let tree = ConditionalOrderTree::new(orders(3)).unwrap();
+
+        let calldata = tree.set_root_calldata(ProofLocation::Emitted);
+        let call = IComposableCoW::setRootCall::abi_decode(&calldata, true).unwrap();
+        let emitted =
+            Vec::<ComposableCoW::ProofWithParams>::abi_decode(&call.proof.data, true).unwrap();
+
+        assert_eq!(call.root, tree.root());
+        assert_eq!(call.proof.location, U256::from(1));
+        assert_eq!(emitted.len(), 3);
+    }
+}
@@ -0,0 +1,213 @@
+//! Conditional orders placed through ComposableCoW, such as TWAP and
+//! stop-loss orders.
+
+mod merkle;
+pub mod stop_loss;
+pub mod twap;
+
+use alloy::{
+    primitives::{Address, B256, Bytes, keccak256},
+    sol,
+    sol_types::{SolCall, SolValue},
+};
+pub use merkle::{ConditionalOrderTree, ProofLocation};
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::order_data::{GPv2Order, OrderData};
+
+sol! {
+    library IConditionalOrder {
+        #[derive(Debug, PartialEq, Eq)]
+        struct ConditionalOrderParams {
+            address handler;
+            bytes32 salt;
+            bytes staticInput;
+        }
+    }
+
+    library ComposableCoW {
+        #[derive(Debug, PartialEq, Eq)]
+        struct Proof {
+            uint256 location;
+            bytes data;
+        }
+
+        #[derive(Debug, PartialEq, Eq)]
+        struct PayloadStruct {
+            bytes32[] proof;
+            IConditionalOrder.ConditionalOrderParams params;
+            bytes offchainInput;
+        }
+
+        #[derive(Debug, PartialEq, Eq)]
+        struct ProofWithParams {
+            bytes32[] proof;
+            IConditionalOrder.ConditionalOrderParams params;
+        }
+    }
+
+    interface IComposableCoW {
+        function create(IConditionalOrder.ConditionalOrderParams params, bool dispatch) external;
+        function createWithContext(
+            IConditionalOrder.ConditionalOrderParams params,
+            address factory,
+            bytes data,
+            bool dispatch
+        ) external;
+        function remove(bytes32 singleOrderHash) external;
+        function setRoot(bytes32 root, ComposableCoW.Proof proof) external;
+        function setRootWithContext(
+            bytes32 root,
+            ComposableCoW.Proof proof,
+            address factory,
+            bytes data
+        ) external;
+    }
+}
+
+/// Parameters uniquely identifying a conditional order.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConditionalOrderParams {
+    /// Contract implementing the order logic, e.g. the TWAP handler.
+    pub handler: Address,
+    /// Salt allowing the same order to be created more than once.
+    pub salt: B256,
+    /// Handler specific ABI-encoded order data.
+    pub static_input: Bytes,
+}
+
+impl ConditionalOrderParams {
+    pub fn new(handler: Address, salt: B256, static_input: Bytes) -> Self {
+        Self { handler, salt, static_input }
+    }
+
+    fn to_sol(&self) -> IConditionalOrder::ConditionalOrderParams {
+        IConditionalOrder::ConditionalOrderParams {
+            handler: self.handler,
+            salt: self.salt,
+            staticInput: self.static_input.clone(),
+        }
+    }
+
+    /// Hash under which ComposableCoW stores single orders, used as the key
+    /// of `singleOrders` and as the argument to `remove`.
+    pub fn id(&self) -> B256 {
+        keccak256(self.to_sol().abi_encode())
+    }
+
+    /// Calldata for `ComposableCoW.create`, authorising this single order.
+    pub fn create_calldata(&self, dispatch: bool) -> Bytes {
+        IComposableCoW::createCall { params: self.to_sol(), dispatch }.abi_encode().into()
+    }
+
+    /// Calldata for `ComposableCoW.createWithContext`, authorising this single
+    /// order and storing the value produced by the `factory` value factory.
+    pub fn create_with_context_calldata(
+        &self,
+        factory: Address,
+        data: Bytes,
+        dispatch: bool,
+    ) -> Bytes {
+        IComposableCoW::createWithContextCall { params: self.to_sol(), factory, data, dispatch }
+            .abi_encode()
+            .into()
+    }
+
+    /// Calldata for `ComposableCoW.remove`, revoking this single order.
+    pub fn remove_calldata(&self) -> Bytes {
+        IComposableCoW::removeCall { singleOrderHash: self.id() }.abi_encode().into()
+    }
+}
+
+impl From<IConditionalOrder::ConditionalOrderParams> for ConditionalOrderParams {
+    fn from(params: IConditionalOrder::ConditionalOrderParams) -> Self {
+        Self { handler: params.handler, salt: params.salt, static_input: params.staticInput }
+    }
+}
+
+/// Builds the EIP-1271 signature for a discrete order produced by a
+/// conditional order.
+///
+/// The signature is `abi.encode(order, payload)` as decoded by ComposableCoW.
+/// `proof` is empty for orders authorised with `create` and the merkle proof
+/// for orders authorised with `setRoot`.
+pub fn eip1271_signature(
+    order: &OrderData,
+    params: &ConditionalOrderParams,
+    proof: &[B256],
+    offchain_input: &Bytes,
+) -> Bytes {
+    let payload = ComposableCoW::PayloadStruct {
+        proof: proof.to_vec(),
+        params: params.to_sol(),
+        offchainInput: offchain_input.clone(),
+    };
+    (GPv2Order::Data::from(order), payload).abi_encode_params().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{U256, address, b256, bytes};
+
+    use super::*;
+    use crate::primitives::order_data::OrderKind;
+
+    fn params() -> ConditionalOrderParams {
+        ConditionalOrderParams::new(
+            address!("0x6cF1e9cA41f7611dEf408122793c358a3d11E5a5"),
+            b256!("0x0000000000000000000000000000000000000000000000000000000000000001"),
+            bytes!("0xdeadbeef"),
+        )
+    }
+
+    #[test]
+    fn test_id_is_hash_of_encoded_params() {
+        let params = params();
+        let encoded = params.to_sol().abi_encode();
+
+        // abi.encode of a dynamic struct starts with the offset to its tail
+        assert_eq!(U256::from_be_slice(&encoded[..32]), U256::from(32));
+        assert_eq!(params.id(), keccak256(encoded));
+    }
+
+    #[test]
+    fn test_create_calldata_round_trip() {
+        let params = params();
+
+        let calldata = params.create_calldata(true);
+        let call = IComposableCoW::createCall::abi_decode(&calldata, true).unwrap();
+
+        assert_eq!(&calldata[..4], IComposableCoW::createCall::SELECTOR.as_slice());
+        assert_eq!(ConditionalOrderParams::from(call.params), params);
+        assert!(call.dispatch);
+    }
+
+    #[test]
+    fn test_remove_calldata_uses_order_id() {
+        let params = params();
+
+        let calldata = params.remove_calldata();
+        let call = IComposableCoW::removeCall::abi_decode(&calldata, true).unwrap();
+
+        assert_eq!(call.singleOrderHash, params.id());
+    }
+
+    #[test]
+    fn test_eip1271_signature_round_trip() {
+        let params = params();
+        let order =
+            OrderData { kind: OrderKind::Sell, valid_to: 1_700_000_000, ..Default::default() };
+        let proof = vec![B256::repeat_byte(1), B256::repeat_byte(2)];
+
+        let signature = eip1271_signature(&order, &params, &proof, &Bytes::new());
+        let (decoded_order, payload) =
+            <(GPv2Order::Data, ComposableCoW::PayloadStruct)>::abi_decode_params(&signature, true)
+                .unwrap();
+
+        assert_eq!(OrderData::try_from(&decoded_order).unwrap(), order);
+        assert_eq!(payload.proof, proof);
+        assert_eq!(ConditionalOrderParams::from(payload.params), params);
+        assert!(payload.offchainInput.is_empty());
+    }
+}
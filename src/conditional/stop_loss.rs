@@ -0,0 +1,200 @@
+use alloy::{
+    primitives::{Address, B256, Bytes, I256, U256, address},
+    sol,
+    sol_types::SolValue,
+};
+use eyre::{Result, WrapErr, eyre};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    conditional::ConditionalOrderParams,
+    primitives::{
+        app_data::AppDataHash,
+        order_data::{BuyTokenDestination, OrderData, OrderKind, SellTokenSource},
+    },
+};
+
+/// Address of the stop-loss conditional order handler.
+pub const STOP_LOSS_HANDLER: Address = address!("0x412c36e5011cd2517016d243a2dfb37f73a242e7");
+
+sol! {
+    library StopLossOrder {
+        #[derive(Debug, PartialEq, Eq)]
+        struct Data {
+            address sellToken;
+            address buyToken;
+            uint256 sellAmount;
+            uint256 buyAmount;
+            bytes32 appData;
+            address receiver;
+            bool isSellOrder;
+            bool isPartiallyFillable;
+            uint32 validTo;
+            address sellTokenPriceOracle;
+            address buyTokenPriceOracle;
+            int256 strike;
+            uint256 maxTimeSinceLastOracleUpdate;
+        }
+    }
+}
+
+/// Static input of a stop-loss order, tradeable once the oracle price of the
+/// sell token in terms of the buy token drops to or below `strike`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StopLossData {
+    pub sell_token: Address,
+    pub buy_token: Address,
+    pub sell_amount: U256,
+    pub buy_amount: U256,
+    pub app_data: AppDataHash,
+    pub receiver: Address,
+    pub is_sell_order: bool,
+    pub is_partially_fillable: bool,
+    pub valid_to: u32,
+    /// Chainlink compatible oracle for the sell token.
+    pub sell_token_price_oracle: Address,
+    /// Chainlink compatible oracle for the buy token.
+    pub buy_token_price_oracle: Address,
+    /// Strike price, scaled to 18 decimals.
+    pub strike: I256,
+    /// Maximum age of oracle answers, in seconds.
+    pub max_time_since_last_oracle_update: U256,
+}
+
+impl StopLossData {
+    /// Checks the order against the constraints enforced by the handler.
+    pub fn validate(&self) -> Result<()> {
+        if self.sell_token == self.buy_token {
+            return Err(eyre!("Sell and buy tokens must differ"));
+        }
+        if self.sell_amount.is_zero() || self.buy_amount.is_zero() {
+            return Err(eyre!("Sell and buy amounts must be positive"));
+        }
+        if self.sell_token_price_oracle.is_zero() || self.buy_token_price_oracle.is_zero() {
+            return Err(eyre!("Price oracles must be set"));
+        }
+        Ok(())
+    }
+
+    fn to_sol(&self) -> StopLossOrder::Data {
+        StopLossOrder::Data {
+            sellToken: self.sell_token,
+            buyToken: self.buy_token,
+            sellAmount: self.sell_amount,
+            buyAmount: self.buy_amount,
+            appData: self.app_data.0.into(),
+            receiver: self.receiver,
+            isSellOrder: self.is_sell_order,
+            isPartiallyFillable: self.is_partially_fillable,
+            validTo: self.valid_to,
+            sellTokenPriceOracle: self.sell_token_price_oracle,
+            buyTokenPriceOracle: self.buy_token_price_oracle,
+            strike: self.strike,
+            maxTimeSinceLastOracleUpdate: self.max_time_since_last_oracle_update,
+        }
+    }
+
+    /// ABI-encoded static input for the stop-loss handler.
+    pub fn static_input(&self) -> Bytes {
+        self.to_sol().abi_encode().into()
+    }
+
+    /// Decodes stop-loss static input.
+    pub fn decode(static_input: &[u8]) -> Result<Self> {
+        let data = StopLossOrder::Data::abi_decode(static_input, true)
+            .wrap_err("Failed to decode stop-loss static input")?;
+        Ok(Self {
+            sell_token: data.sellToken,
+            buy_token: data.buyToken,
+            sell_amount: data.sellAmount,
+            buy_amount: data.buyAmount,
+            app_data: AppDataHash(data.appData.0),
+            receiver: data.receiver,
+            is_sell_order: data.isSellOrder,
+            is_partially_fillable: data.isPartiallyFillable,
+            valid_to: data.validTo,
+            sell_token_price_oracle: data.sellTokenPriceOracle,
+            buy_token_price_oracle: data.buyTokenPriceOracle,
+            strike: data.strike,
+            max_time_since_last_oracle_update: data.maxTimeSinceLastOracleUpdate,
+        })
+    }
+
+    /// Conditional order parameters for this stop-loss.
+    pub fn to_params(&self, salt: B256) -> ConditionalOrderParams {
+        ConditionalOrderParams::new(STOP_LOSS_HANDLER, salt, self.static_input())
+    }
+
+    /// Calldata creating this stop-loss with ComposableCoW.
+    pub fn create_calldata(&self, salt: B256, dispatch: bool) -> Result<Bytes> {
+        self.validate()?;
+        Ok(self.to_params(salt).create_calldata(dispatch))
+    }
+
+    /// The discrete order the handler produces once the strike is reached.
+    pub fn order(&self) -> OrderData {
+        OrderData {
+            sell_token: self.sell_token,
+            buy_token: self.buy_token,
+            receiver: self.receiver,
+            sell_amount: self.sell_amount,
+            buy_amount: self.buy_amount,
+            valid_to: self.valid_to,
+            app_data: self.app_data,
+            fee_amount: U256::ZERO,
+            kind: if self.is_sell_order { OrderKind::Sell } else { OrderKind::Buy },
+            partially_fillable: self.is_partially_fillable,
+            sell_token_balance: SellTokenSource::Erc20,
+            buy_token_balance: BuyTokenDestination::Erc20,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stop_loss() -> StopLossData {
+        StopLossData {
+            sell_token: address!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+            buy_token: address!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+            sell_amount: U256::from(10).pow(U256::from(18)),
+            buy_amount: U256::from(1_500_000_000u64),
+            app_data: AppDataHash::default(),
+            receiver: Address::ZERO,
+            is_sell_order: true,
+            is_partially_fillable: false,
+            valid_to: 1_700_000_000,
+            sell_token_price_oracle: address!("0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"),
+            buy_token_price_oracle: address!("0x8fFfFfd4AfB6115b954Bd326cbe7B4BA576818f6"),
+            strike: I256::try_from(1_500).unwrap(),
+            max_time_since_last_oracle_update: U256::from(15 * 60),
+        }
+    }
+
+    #[test]
+    fn test_static_input_round_trip() {
+        let stop_loss = stop_loss();
+        let static_input = stop_loss.static_input();
+
+        assert_eq!(static_input.len(), 13 * 32);
+        assert_eq!(StopLossData::decode(&static_input).unwrap(), stop_loss);
+    }
+
+    #[test]
+    fn test_order_kind_follows_is_sell_order() {
+        assert_eq!(stop_loss().order().kind, OrderKind::Sell);
+        assert_eq!(
+            StopLossData { is_sell_order: false, ..stop_loss() }.order().kind,
+            OrderKind::Buy
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_missing_oracle() {
+        let stop_loss = StopLossData { buy_token_price_oracle: Address::ZERO, ..stop_loss() };
+
+        assert!(stop_loss.create_calldata(B256::ZERO, true).is_err());
+    }
+}
@@ -0,0 +1,469 @@
+use alloy::{
+    primitives::{Address, B256, Bytes, U256, address},
+    sol,
+    sol_types::{Eip712Domain, SolValue},
+};
+use eyre::{Result, WrapErr, eyre};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    conditional::ConditionalOrderParams,
+    models::trade::Trade,
+    primitives::{
+        app_data::AppDataHash,
+        order_data::{BuyTokenDestination, OrderData, OrderKind, SellTokenSource},
+        order_uid::OrderUid,
+    },
+};
+
+/// Address of the TWAP conditional order handler.
+pub const TWAP_HANDLER: Address = address!("0x6cF1e9cA41f7611dEf408122793c358a3d11E5a5");
+
+/// Value factory storing the block timestamp at creation, used as start time
+/// of TWAPs that start when the creation transaction is mined.
+pub const CURRENT_BLOCK_TIMESTAMP_FACTORY: Address =
+    address!("0x52eD56Da04309Aca4c3FECC595298d80C2f16BAc");
+
+/// Maximum duration of a single part accepted by the handler.
+const MAX_PART_DURATION: u64 = 365 * 24 * 60 * 60;
+
+sol! {
+    library TWAPOrder {
+        #[derive(Debug, PartialEq, Eq)]
+        struct Data {
+            address sellToken;
+            address buyToken;
+            address receiver;
+            uint256 partSellAmount;
+            uint256 minPartLimit;
+            uint256 t0;
+            uint256 n;
+            uint256 t;
+            uint256 span;
+            bytes32 appData;
+        }
+    }
+}
+
+/// Static input of a TWAP order, as stored by the TWAP handler.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TwapData {
+    pub sell_token: Address,
+    pub buy_token: Address,
+    pub receiver: Address,
+    /// Amount of sell token sold by each part.
+    pub part_sell_amount: U256,
+    /// Minimum amount of buy token bought by each part.
+    pub min_part_limit: U256,
+    /// Start timestamp, zero to start when the creation transaction is mined.
+    pub t0: u64,
+    /// Number of parts.
+    pub n: u64,
+    /// Time between the start of two parts, in seconds.
+    pub t: u64,
+    /// Duration each part is valid for, zero for the full part interval.
+    pub span: u64,
+    pub app_data: AppDataHash,
+}
+
+impl TwapData {
+    /// Checks the order against the constraints enforced by the handler.
+    pub fn validate(&self) -> Result<()> {
+        if self.sell_token == self.buy_token {
+            return Err(eyre!("Sell and buy tokens must differ"));
+        }
+        if self.sell_token.is_zero() || self.buy_token.is_zero() {
+            return Err(eyre!("Sell and buy tokens must be set"));
+        }
+        if self.part_sell_amount.is_zero() {
+            return Err(eyre!("Part sell amount must be positive"));
+        }
+        if self.min_part_limit.is_zero() {
+            return Err(eyre!("Minimum part limit must be positive"));
+        }
+        if self.t0 >= u64::from(u32::MAX) {
+            return Err(eyre!("Start time must fit in 32 bits"));
+        }
+        if self.n <= 1 || self.n > u64::from(u32::MAX) {
+            return Err(eyre!("Number of parts must be greater than 1 and fit in 32 bits"));
+        }
+        if self.t == 0 || self.t > MAX_PART_DURATION {
+            return Err(eyre!("Part duration must be positive and at most 365 days"));
+        }
+        if self.span > self.t {
+            return Err(eyre!("Span must not exceed the part duration"));
+        }
+        Ok(())
+    }
+
+    fn to_sol(&self) -> TWAPOrder::Data {
+        TWAPOrder::Data {
+            sellToken: self.sell_token,
+            buyToken: self.buy_token,
+            receiver: self.receiver,
+            partSellAmount: self.part_sell_amount,
+            minPartLimit: self.min_part_limit,
+            t0: U256::from(self.t0),
+            n: U256::from(self.n),
+            t: U256::from(self.t),
+            span: U256::from(self.span),
+            appData: self.app_data.0.into(),
+        }
+    }
+
+    /// ABI-encoded static input for the TWAP handler.
+    pub fn static_input(&self) -> Bytes {
+        self.to_sol().abi_encode().into()
+    }
+
+    /// Decodes TWAP static input, e.g. from a `ConditionalOrderCreated` event.
+    pub fn decode(static_input: &[u8]) -> Result<Self> {
+        let data = TWAPOrder::Data::abi_decode(static_input, true)
+            .wrap_err("Failed to decode TWAP static input")?;
+        let to_u64 = |value: U256, name: &str| {
+            u64::try_from(value).map_err(|_| eyre!("TWAP {} does not fit in 64 bits", name))
+        };
+        Ok(Self {
+            sell_token: data.sellToken,
+            buy_token: data.buyToken,
+            receiver: data.receiver,
+            part_sell_amount: data.partSellAmount,
+            min_part_limit: data.minPartLimit,
+            t0: to_u64(data.t0, "t0")?,
+            n: to_u64(data.n, "n")?,
+            t: to_u64(data.t, "t")?,
+            span: to_u64(data.span, "span")?,
+            app_data: AppDataHash(data.appData.0),
+        })
+    }
+
+    /// Conditional order parameters for this TWAP.
+    pub fn to_params(&self, salt: B256) -> ConditionalOrderParams {
+        ConditionalOrderParams::new(TWAP_HANDLER, salt, self.static_input())
+    }
+
+    /// Calldata creating this TWAP with ComposableCoW. TWAPs without a start
+    /// time are created with the current block timestamp as context.
+    pub fn create_calldata(&self, salt: B256, dispatch: bool) -> Result<Bytes> {
+        self.validate()?;
+        let params = self.to_params(salt);
+        if self.t0 == 0 {
+            Ok(params.create_with_context_calldata(
+                CURRENT_BLOCK_TIMESTAMP_FACTORY,
+                Bytes::new(),
+                dispatch,
+            ))
+        } else {
+            Ok(params.create_calldata(dispatch))
+        }
+    }
+
+    /// Timestamp from which part `index` can be traded.
+    fn part_start(&self, index: u64, start_time: u64) -> Result<u64> {
+        index
+            .checked_mul(self.t)
+            .and_then(|offset| start_time.checked_add(offset))
+            .ok_or_else(|| eyre!("Part {} starts after the 64 bit timestamp range", index))
+    }
+
+    /// The discrete order the handler produces for part `index`, starting at
+    /// `start_time`. Use `t0` as start time unless the TWAP starts at mining
+    /// time, in which case it is the block timestamp stored on creation.
+    pub fn part(&self, index: u64, start_time: u64) -> Result<OrderData> {
+        if index >= self.n {
+            return Err(eyre!("Part {} out of range, TWAP has {} parts", index, self.n));
+        }
+
+        let end = if self.span == 0 {
+            self.part_start(index + 1, start_time)?
+        } else {
+            self.part_start(index, start_time)?
+                .checked_add(self.span)
+                .ok_or_else(|| eyre!("Part {} ends after the 64 bit timestamp range", index))?
+        };
+        let valid_to = end.saturating_sub(1);
+
+        Ok(OrderData {
+            sell_token: self.sell_token,
+            buy_token: self.buy_token,
+            receiver: self.receiver,
+            sell_amount: self.part_sell_amount,
+            buy_amount: self.min_part_limit,
+            valid_to: u32::try_from(valid_to)
+                .map_err(|_| eyre!("Part {} expires after the 32 bit timestamp range", index))?,
+            app_data: self.app_data,
+            fee_amount: U256::ZERO,
+            kind: OrderKind::Sell,
+            partially_fillable: false,
+            sell_token_balance: SellTokenSource::Erc20,
+            buy_token_balance: BuyTokenDestination::Erc20,
+        })
+    }
+}
+
+/// Plans a TWAP from its total sell amount, to preview the orders each part
+/// will create before signing.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TwapPlanner {
+    pub sell_token: Address,
+    pub buy_token: Address,
+    pub receiver: Address,
+    /// Amount of sell token sold over all parts.
+    pub total_sell_amount: U256,
+    pub number_of_parts: u64,
+    /// Time between the start of two parts, in seconds.
+    pub part_duration: u64,
+    /// Duration each part is valid for, zero for the full part duration.
+    pub span: u64,
+    /// Minimum amount of buy token bought by each part.
+    pub min_part_limit: U256,
+    /// Start timestamp, `None` to start when the creation transaction is
+    /// mined.
+    pub start_time: Option<u64>,
+    pub app_data: AppDataHash,
+}
+
+impl TwapPlanner {
+    /// Static input of the planned TWAP, validated against the handler
+    /// constraints.
+    pub fn twap_data(&self) -> Result<TwapData> {
+        if self.number_of_parts == 0 {
+            return Err(eyre!("Number of parts must be positive"));
+        }
+        let data = TwapData {
+            sell_token: self.sell_token,
+            buy_token: self.buy_token,
+            receiver: self.receiver,
+            part_sell_amount: self.total_sell_amount / U256::from(self.number_of_parts),
+            min_part_limit: self.min_part_limit,
+            t0: self.start_time.unwrap_or_default(),
+            n: self.number_of_parts,
+            t: self.part_duration,
+            span: self.span,
+            app_data: self.app_data,
+        };
+        data.validate()?;
+        Ok(data)
+    }
+
+    /// Computes the orders of every part for `owner`, the Safe holding the
+    /// TWAP. TWAPs starting at mining time need the block timestamp of the
+    /// creation transaction, or an estimate of it, as `mined_at`.
+    pub fn schedule(
+        &self,
+        owner: Address,
+        domain: &Eip712Domain,
+        mined_at: Option<u64>,
+    ) -> Result<TwapSchedule> {
+        let data = self.twap_data()?;
+        let start_time = self.start_time.or(mined_at).ok_or_else(|| {
+            eyre!("TWAP starts at mining time, a start time is required to plan its parts")
+        })?;
+
+        let parts = (0..data.n)
+            .map(|index| {
+                let order = data.part(index, start_time)?;
+                Ok(TwapPart {
+                    index,
+                    valid_from: data.part_start(index, start_time)?,
+                    uid: order.uid(domain, owner),
+                    order,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(TwapSchedule {
+            unsold_remainder: self.total_sell_amount - data.part_sell_amount * U256::from(data.n),
+            data,
+            parts,
+        })
+    }
+}
+
+/// A single part of a TWAP and the order it creates.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TwapPart {
+    pub index: u64,
+    /// Timestamp from which the part can be traded.
+    pub valid_from: u64,
+    pub order: OrderData,
+    pub uid: OrderUid,
+}
+
+/// Orders created by all parts of a TWAP.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TwapSchedule {
+    pub data: TwapData,
+    pub parts: Vec<TwapPart>,
+    /// Sell amount that is not sold because it does not divide evenly
+    /// between the parts.
+    pub unsold_remainder: U256,
+}
+
+impl TwapSchedule {
+    /// Part that created the order with the given UID.
+    pub fn part_by_uid(&self, uid: &OrderUid) -> Option<&TwapPart> {
+        self.parts.iter().find(|part| part.uid == *uid)
+    }
+
+    /// Matches trades to the parts that created them, ignoring unrelated
+    /// trades.
+    pub fn match_trades<'a>(&self, trades: &'a [Trade]) -> Vec<(&TwapPart, &'a Trade)> {
+        trades
+            .iter()
+            .filter_map(|trade| self.part_by_uid(&trade.order_uid).map(|part| (part, trade)))
+            .collect()
+    }
+
+    /// Timestamp after which no part can be traded anymore.
+    pub fn end_time(&self) -> u64 {
+        self.parts.last().map(|part| u64::from(part.order.valid_to)).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{primitives::TxHash, sol_types::SolCall};
+
+    use super::*;
+    use crate::{conditional::IComposableCoW, config::Network};
+
+    fn twap() -> TwapData {
+        TwapData {
+            sell_token: address!("0x6B175474E89094C44Da98b954EedeAC495271d0F"),
+            buy_token: address!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+            receiver: Address::ZERO,
+            part_sell_amount: U256::from(100),
+            min_part_limit: U256::from(1),
+            t0: 1_700_000_000,
+            n: 10,
+            t: 3600,
+            span: 0,
+            app_data: AppDataHash::default(),
+        }
+    }
+
+    #[test]
+    fn test_static_input_round_trip() {
+        let twap = twap();
+        let static_input = twap.static_input();
+
+        assert_eq!(static_input.len(), 10 * 32);
+        assert_eq!(TwapData::decode(&static_input).unwrap(), twap);
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_orders() {
+        assert!(twap().validate().is_ok());
+        assert!(TwapData { buy_token: twap().sell_token, ..twap() }.validate().is_err());
+        assert!(TwapData { part_sell_amount: U256::ZERO, ..twap() }.validate().is_err());
+        assert!(TwapData { n: 1, ..twap() }.validate().is_err());
+        assert!(TwapData { t: 0, ..twap() }.validate().is_err());
+        assert!(TwapData { span: 3601, ..twap() }.validate().is_err());
+    }
+
+    #[test]
+    fn test_create_calldata_uses_context_without_start_time() {
+        let salt = B256::repeat_byte(1);
+
+        let calldata = TwapData { t0: 0, ..twap() }.create_calldata(salt, true).unwrap();
+        let call = IComposableCoW::createWithContextCall::abi_decode(&calldata, true).unwrap();
+
+        assert_eq!(call.factory, CURRENT_BLOCK_TIMESTAMP_FACTORY);
+        assert_eq!(call.params.handler, TWAP_HANDLER);
+        assert_eq!(call.params.salt, salt);
+    }
+
+    #[test]
+    fn test_part_valid_to() {
+        let twap = twap();
+
+        assert_eq!(twap.part(0, twap.t0).unwrap().valid_to, 1_700_003_599);
+        assert_eq!(twap.part(9, twap.t0).unwrap().valid_to, 1_700_035_999);
+        assert!(twap.part(10, twap.t0).is_err());
+
+        let with_span = TwapData { span: 600, ..twap };
+        assert_eq!(with_span.part(1, with_span.t0).unwrap().valid_to, 1_700_004_199);
+    }
+
+    fn planner() -> TwapPlanner {
+        TwapPlanner {
+            sell_token: twap().sell_token,
+            buy_token: twap().buy_token,
+            receiver: Address::ZERO,
+            total_sell_amount: U256::from(1_005),
+            number_of_parts: 10,
+            part_duration: 3600,
+            span: 0,
+            min_part_limit: U256::from(1),
+            start_time: Some(1_700_000_000),
+            app_data: AppDataHash::default(),
+        }
+    }
+
+    #[test]
+    fn test_planner_splits_total_amount() {
+        let owner = address!("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045");
+
+        let schedule =
+            planner().schedule(owner, &Network::Mainnet.settlement_domain(), None).unwrap();
+
+        assert_eq!(schedule.data, twap());
+        assert_eq!(schedule.parts.len(), 10);
+        assert_eq!(schedule.unsold_remainder, U256::from(5));
+        assert_eq!(schedule.parts[3].valid_from, 1_700_010_800);
+        assert_eq!(schedule.parts[3].order.sell_amount, U256::from(100));
+        assert_eq!(schedule.parts[3].uid.owner(), owner);
+        assert_eq!(schedule.end_time(), 1_700_035_999);
+    }
+
+    #[test]
+    fn test_planner_requires_start_time_for_mining_time_start() {
+        let planner = TwapPlanner { start_time: None, ..planner() };
+        let domain = Network::Mainnet.settlement_domain();
+
+        assert!(planner.schedule(Address::ZERO, &domain, None).is_err());
+        assert_eq!(
+            planner.schedule(Address::ZERO, &domain, Some(1_700_000_000)).unwrap().parts[0]
+                .valid_from,
+            1_700_000_000
+        );
+    }
+
+    #[test]
+    fn test_schedule_matches_trades_to_parts() {
+        let schedule =
+            planner().schedule(Address::ZERO, &Network::Mainnet.settlement_domain(), None).unwrap();
+        let trade = |order_uid| Trade {
+            block_number: 1,
+            order_uid,
+            log_index: 0,
+            sell_token: twap().sell_token,
+            buy_token: twap().buy_token,
+            sell_amount: U256::from(100),
+            sell_amount_before_fees: U256::from(100),
+            buy_amount: U256::from(1),
+            tx_hash: TxHash::ZERO,
+            executed_protocol_fees: vec![],
+        };
+        let trades = vec![trade(schedule.parts[2].uid), trade(OrderUid::new(Default::default()))];
+
+        let matched = schedule.match_trades(&trades);
+
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].0.index, 2);
+    }
+
+    #[test]
+    fn test_schedule_rejects_overflowing_times() {
+        let domain = Network::Mainnet.settlement_domain();
+        let planner = TwapPlanner { start_time: None, ..planner() };
+
+        assert!(planner.schedule(Address::ZERO, &domain, Some(u64::MAX - 1)).is_err());
+        assert!(TwapData { t: u64::MAX, ..twap() }.part(2, 0).is_err());
+    }
+}
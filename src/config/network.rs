@@ -1,7 +1,11 @@
 use std::{fmt, str};
 
-use log::{debug, warn};
+use alloy::{
+    primitives::{Address, address},
+    sol_types::{Eip712Domain, eip712_domain},
+};
 use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
 
 // Orderbook API URLs
 const MAINNET_PROD_API_URL: &str = "https://api.cow.fi/mainnet";
@@ -16,6 +20,11 @@ const GNOSIS_PROD_API_URL: &str = "https://api.cow.fi/xdai";
 const GNOSIS_STAGING_API_URL: &str = "https://barn.api.cow.fi/xdai";
 const LOCAL_API_URL: &str = "http://localhost:8080";
 
+// Subgraph IDs on The Graph's decentralized network
+const MAINNET_SUBGRAPH_ID: &str = "8mdwJG7YCSwqfxUbhCypZvoubeZcFVpCHb4zmHhvuKTD";
+const ARBITRUM_SUBGRAPH_ID: &str = "CQ8g2uJCjdAkUSNkVbd9oqqRP2GALKu1jJCD3fyY5tdc";
+const GNOSIS_SUBGRAPH_ID: &str = "HTQcP2gLuAy235CMNE8ApN4cbzpLVjjNxtCAUfpzRubq";
+
 // RPC URLs
 const MAINNET_RPC_URL: &str = "https://mainnet.infura.io/v3/";
 const SEPOLIA_RPC_URL: &str = "https://sepolia.infura.io/v3/";
@@ -24,7 +33,37 @@ const ARBITRUM_RPC_URL: &str = "https://arbitrum.infura.io/v3/";
 const GNOSIS_RPC_URL: &str = "https://xdai.infura.io/v3/";
 const LOCAL_RPC_URL: &str = "http://localhost:8545";
 
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+// Chain IDs
+const MAINNET_CHAIN_ID: u64 = 1;
+const SEPOLIA_CHAIN_ID: u64 = 11155111;
+const BASE_CHAIN_ID: u64 = 8453;
+const ARBITRUM_CHAIN_ID: u64 = 42161;
+const GNOSIS_CHAIN_ID: u64 = 100;
+const LOCAL_CHAIN_ID: u64 = 31337;
+
+// Protocol contracts (deterministically deployed, same address on every chain)
+const SETTLEMENT_CONTRACT: Address = address!("0x9008D19f58AAbD9eD0D60971565AA8510560ab41");
+const VAULT_RELAYER: Address = address!("0xC92E8bdf79f0507f65a392b0ab4667716BFE0110");
+const COMPOSABLE_COW: Address = address!("0xfdaFc9d1902f4e0b84f65F49f244b32b31013b74");
+const BALANCER_VAULT: Address = address!("0xBA12222222228d8Ba445958a75a0704d566BF2C8");
+const ETH_FLOW_PROD: Address = address!("0xbA3cB449bD2B4ADddBc894D8697F5170800EAdeC");
+const ETH_FLOW_STAGING: Address = address!("0x04501b9b1D52e67f6862d157E00D13419D2D6E95");
+
+// Wrapped native tokens
+const MAINNET_WRAPPED_NATIVE_TOKEN: Address =
+    address!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+const SEPOLIA_WRAPPED_NATIVE_TOKEN: Address =
+    address!("0xfFf9976782d46CC05630D1f6eBAb18b2324d6B14");
+const BASE_WRAPPED_NATIVE_TOKEN: Address = address!("0x4200000000000000000000000000000000000006");
+const ARBITRUM_WRAPPED_NATIVE_TOKEN: Address =
+    address!("0x82aF49447D8a07e3bd95BD0d56f35241523fBab1");
+const GNOSIS_WRAPPED_NATIVE_TOKEN: Address = address!("0xe91D153E0b41518A2Ce8Dd3D7944Fa863463a97d");
+
+// EIP-712 domain of the settlement contract
+const DOMAIN_NAME: &str = "Gnosis Protocol";
+const DOMAIN_VERSION: &str = "v2";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[non_exhaustive]
 pub enum Network {
     #[default]
@@ -68,6 +107,112 @@ impl Network {
             Network::Local => LOCAL_RPC_URL,
         }
     }
+
+    /// ID of the CoW Protocol subgraph indexing the network, if deployed.
+    pub const fn subgraph_id(&self) -> Option<&'static str> {
+        match self {
+            Network::Mainnet => Some(MAINNET_SUBGRAPH_ID),
+            Network::Arbitrum => Some(ARBITRUM_SUBGRAPH_ID),
+            Network::Gnosis => Some(GNOSIS_SUBGRAPH_ID),
+            _ => None,
+        }
+    }
+
+    pub const fn chain_id(&self) -> u64 {
+        match self {
+            Network::Mainnet | Network::MainnetStaging => MAINNET_CHAIN_ID,
+            Network::Sepolia | Network::SepoliaStaging => SEPOLIA_CHAIN_ID,
+            Network::Base | Network::BaseStaging => BASE_CHAIN_ID,
+            Network::Arbitrum | Network::ArbitrumStaging => ARBITRUM_CHAIN_ID,
+            Network::Gnosis | Network::GnosisStaging => GNOSIS_CHAIN_ID,
+            Network::Local => LOCAL_CHAIN_ID,
+        }
+    }
+
+    /// Whether the network uses the staging (barn) orderbook.
+    pub const fn is_staging(&self) -> bool {
+        matches!(
+            self,
+            Network::MainnetStaging
+                | Network::SepoliaStaging
+                | Network::BaseStaging
+                | Network::ArbitrumStaging
+                | Network::GnosisStaging
+        )
+    }
+
+    /// The production variant of the network.
+    pub const fn prod(&self) -> Network {
+        match self {
+            Network::Mainnet | Network::MainnetStaging => Network::Mainnet,
+            Network::Sepolia | Network::SepoliaStaging => Network::Sepolia,
+            Network::Base | Network::BaseStaging => Network::Base,
+            Network::Arbitrum | Network::ArbitrumStaging => Network::Arbitrum,
+            Network::Gnosis | Network::GnosisStaging => Network::Gnosis,
+            Network::Local => Network::Local,
+        }
+    }
+
+    /// The staging variant of the network, if any.
+    pub const fn staging(&self) -> Option<Network> {
+        match self {
+            Network::Mainnet | Network::MainnetStaging => Some(Network::MainnetStaging),
+            Network::Sepolia | Network::SepoliaStaging => Some(Network::SepoliaStaging),
+            Network::Base | Network::BaseStaging => Some(Network::BaseStaging),
+            Network::Arbitrum | Network::ArbitrumStaging => Some(Network::ArbitrumStaging),
+            Network::Gnosis | Network::GnosisStaging => Some(Network::GnosisStaging),
+            Network::Local => None,
+        }
+    }
+
+    /// Address of the GPv2Settlement contract.
+    pub const fn settlement_contract(&self) -> Address {
+        SETTLEMENT_CONTRACT
+    }
+
+    /// Address of the GPv2VaultRelayer contract, the spender of sell tokens.
+    pub const fn vault_relayer(&self) -> Address {
+        VAULT_RELAYER
+    }
+
+    /// Address of the Balancer vault, holding balances of orders selling from
+    /// external or internal balances.
+    pub const fn balancer_vault(&self) -> Address {
+        BALANCER_VAULT
+    }
+
+    /// Address of the ComposableCoW contract used for conditional orders.
+    pub const fn composable_cow(&self) -> Address {
+        COMPOSABLE_COW
+    }
+
+    /// Address of the CoWSwapEthFlow contract used to sell the native token.
+    /// Staging networks use the contract indexed by the staging orderbook.
+    pub const fn eth_flow_contract(&self) -> Address {
+        if self.is_staging() { ETH_FLOW_STAGING } else { ETH_FLOW_PROD }
+    }
+
+    /// Address of the wrapped native token, e.g. WETH on mainnet.
+    pub const fn wrapped_native_token(&self) -> Address {
+        match self {
+            Network::Mainnet | Network::MainnetStaging | Network::Local =>
+                MAINNET_WRAPPED_NATIVE_TOKEN,
+            Network::Sepolia | Network::SepoliaStaging => SEPOLIA_WRAPPED_NATIVE_TOKEN,
+            Network::Base | Network::BaseStaging => BASE_WRAPPED_NATIVE_TOKEN,
+            Network::Arbitrum | Network::ArbitrumStaging => ARBITRUM_WRAPPED_NATIVE_TOKEN,
+            Network::Gnosis | Network::GnosisStaging => GNOSIS_WRAPPED_NATIVE_TOKEN,
+        }
+    }
+
+    /// EIP-712 domain used to sign orders for this network.
+    pub fn settlement_domain(&self) -> Eip712Domain {
+        eip712_domain! {
+            name: DOMAIN_NAME,
+            version: DOMAIN_VERSION,
+            chain_id: self.chain_id(),
+            verifying_contract: self.settlement_contract(),
+        }
+    }
 }
 
 impl str::FromStr for Network {
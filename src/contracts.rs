@@ -0,0 +1,25 @@
+//! Bindings for the external contracts the SDK interacts with.
+
+use alloy::sol;
+
+sol! {
+    #[sol(rpc)]
+    interface IERC20 {
+        function balanceOf(address owner) external view returns (uint256);
+        function allowance(address owner, address spender) external view returns (uint256);
+        function approve(address spender, uint256 amount) external returns (bool);
+    }
+
+    #[sol(rpc)]
+    interface IERC20Metadata {
+        function name() external view returns (string);
+        function symbol() external view returns (string);
+        function decimals() external view returns (uint8);
+    }
+
+    #[sol(rpc)]
+    interface IBalancerVault {
+        function hasApprovedRelayer(address user, address relayer) external view returns (bool);
+        function setRelayerApproval(address sender, address relayer, bool approved) external;
+    }
+}
@@ -0,0 +1,263 @@
+//! Orders selling the native token through the CoWSwapEthFlow contract.
+
+use alloy::{
+    primitives::{Address, Bytes, U256},
+    rpc::types::TransactionRequest,
+    sol,
+    sol_types::SolCall,
+};
+use eyre::{Result, eyre};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::Network,
+    models::response::QuoteResponse,
+    primitives::{
+        app_data::AppDataHash,
+        order_data::{BuyTokenDestination, OrderData, OrderKind, SellTokenSource},
+        order_uid::OrderUid,
+    },
+    quote::QuoteAmountsAndCosts,
+};
+
+sol! {
+    library IEthFlowOrder {
+        #[derive(Debug, PartialEq, Eq)]
+        struct Data {
+            address buyToken;
+            address receiver;
+            uint256 sellAmount;
+            uint256 buyAmount;
+            bytes32 appData;
+            uint256 feeAmount;
+            uint32 validTo;
+            bool partiallyFillable;
+            int64 quoteId;
+        }
+    }
+
+    interface ICoWSwapEthFlow {
+        function createOrder(IEthFlowOrder.Data order) external payable returns (bytes32 orderHash);
+        function invalidateOrder(IEthFlowOrder.Data order) external;
+    }
+}
+
+/// Order selling the native token, placed on-chain by sending the sell amount
+/// to the eth-flow contract, which then owns the resulting CoW order.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EthFlowOrder {
+    pub buy_token: Address,
+    /// Receiver of the buy tokens, must be set as the order owner is the
+    /// eth-flow contract.
+    pub receiver: Address,
+    pub sell_amount: U256,
+    pub buy_amount: U256,
+    pub app_data: AppDataHash,
+    pub fee_amount: U256,
+    /// Expiry enforced by the eth-flow contract, the CoW order itself never
+    /// expires.
+    pub valid_to: u32,
+    pub partially_fillable: bool,
+    /// ID of the quote the order is based on, used by the orderbook to
+    /// attribute the on-chain order.
+    pub quote_id: i64,
+}
+
+impl EthFlowOrder {
+    /// Builds an eth-flow order from a quote for selling the wrapped native
+    /// token of `network`, with the quoted network costs included in the sell
+    /// amount.
+    pub fn from_quote(quote: &QuoteResponse, network: &Network, receiver: Address) -> Result<Self> {
+        let order = &quote.quote;
+        if order.sell_token != network.wrapped_native_token() {
+            return Err(eyre!(
+                "Eth-flow orders must be quoted with the wrapped native token {} as sell token, \
+                 got {}",
+                network.wrapped_native_token(),
+                order.sell_token
+            ));
+        }
+        if order.kind != OrderKind::Sell {
+            return Err(eyre!("Eth-flow orders must be sell orders"));
+        }
+
+        // The orderbook rejects signed fees, so network costs are sold on top
+        // of the quoted sell amount instead
+        let amounts = QuoteAmountsAndCosts::new(order, 0, 0)?.after_slippage;
+        let eth_flow_order = Self {
+            buy_token: order.buy_token,
+            receiver,
+            sell_amount: amounts.sell_amount,
+            buy_amount: amounts.buy_amount,
+            app_data: order.app_data_hash(),
+            fee_amount: U256::ZERO,
+            valid_to: order.valid_to,
+            partially_fillable: order.partially_fillable,
+            quote_id: quote.id,
+        };
+        eth_flow_order.validate()?;
+        Ok(eth_flow_order)
+    }
+
+    /// Checks the order against the constraints enforced by the contract.
+    pub fn validate(&self) -> Result<()> {
+        if self.receiver.is_zero() {
+            return Err(eyre!("Eth-flow orders require a receiver"));
+        }
+        if self.sell_amount.is_zero() {
+            return Err(eyre!("Sell amount must be positive"));
+        }
+        Ok(())
+    }
+
+    fn to_sol(&self) -> IEthFlowOrder::Data {
+        IEthFlowOrder::Data {
+            buyToken: self.buy_token,
+            receiver: self.receiver,
+            sellAmount: self.sell_amount,
+            buyAmount: self.buy_amount,
+            appData: self.app_data.0.into(),
+            feeAmount: self.fee_amount,
+            validTo: self.valid_to,
+            partiallyFillable: self.partially_fillable,
+            quoteId: self.quote_id,
+        }
+    }
+
+    /// Native token amount to send with `createOrder`.
+    pub fn value(&self) -> U256 {
+        self.sell_amount + self.fee_amount
+    }
+
+    /// Calldata for `CoWSwapEthFlow.createOrder`.
+    pub fn create_order_calldata(&self) -> Bytes {
+        ICoWSwapEthFlow::createOrderCall { order: self.to_sol() }.abi_encode().into()
+    }
+
+    /// Calldata for `CoWSwapEthFlow.invalidateOrder`, refunding the sell
+    /// amount of an unfilled order.
+    pub fn invalidate_order_calldata(&self) -> Bytes {
+        ICoWSwapEthFlow::invalidateOrderCall { order: self.to_sol() }.abi_encode().into()
+    }
+
+    /// Transaction creating the order, sending the required value to the
+    /// eth-flow contract of `network`.
+    pub fn create_order_tx(&self, network: &Network) -> TransactionRequest {
+        TransactionRequest::default()
+            .to(network.eth_flow_contract())
+            .value(self.value())
+            .input(self.create_order_calldata().into())
+    }
+
+    /// Transaction invalidating the order on `network`.
+    pub fn invalidate_order_tx(&self, network: &Network) -> TransactionRequest {
+        TransactionRequest::default()
+            .to(network.eth_flow_contract())
+            .input(self.invalidate_order_calldata().into())
+    }
+
+    /// The CoW order created by the eth-flow contract, selling the wrapped
+    /// native token without expiry.
+    pub fn order_data(&self, network: &Network) -> OrderData {
+        OrderData {
+            sell_token: network.wrapped_native_token(),
+            buy_token: self.buy_token,
+            receiver: self.receiver,
+            sell_amount: self.sell_amount,
+            buy_amount: self.buy_amount,
+            valid_to: u32::MAX,
+            app_data: self.app_data,
+            fee_amount: self.fee_amount,
+            kind: OrderKind::Sell,
+            partially_fillable: self.partially_fillable,
+            sell_token_balance: SellTokenSource::Erc20,
+            buy_token_balance: BuyTokenDestination::Erc20,
+        }
+    }
+
+    /// UID of the order on `network`, owned by the eth-flow contract.
+    pub fn uid(&self, network: &Network) -> OrderUid {
+        self.order_data(network).uid(&network.settlement_domain(), network.eth_flow_contract())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::address;
+
+    use super::*;
+
+    fn order() -> EthFlowOrder {
+        EthFlowOrder {
+            buy_token: address!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+            receiver: address!("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045"),
+            sell_amount: U256::from(10).pow(U256::from(18)),
+            buy_amount: U256::from(1_500_000_000u64),
+            app_data: AppDataHash::default(),
+            fee_amount: U256::ZERO,
+            valid_to: 1_700_000_000,
+            partially_fillable: false,
+            quote_id: 42,
+        }
+    }
+
+    #[test]
+    fn test_create_order_tx_sends_sell_amount() {
+        let order = order();
+
+        let tx = order.create_order_tx(&Network::Mainnet);
+
+        assert_eq!(tx.to, Some(Network::Mainnet.eth_flow_contract().into()));
+        assert_eq!(tx.value, Some(order.sell_amount));
+        let call =
+            ICoWSwapEthFlow::createOrderCall::abi_decode(tx.input.input().unwrap(), true).unwrap();
+        assert_eq!(call.order, order.to_sol());
+    }
+
+    #[test]
+    fn test_uid_is_owned_by_eth_flow_contract_without_expiry() {
+        let uid = order().uid(&Network::GnosisStaging);
+
+        assert_eq!(uid.owner(), Network::GnosisStaging.eth_flow_contract());
+        assert_eq!(uid.valid_to(), u32::MAX);
+        assert_ne!(uid, order().uid(&Network::Gnosis));
+    }
+
+    #[test]
+    fn test_from_quote_requires_wrapped_native_sell_token() {
+        let quote = |sell_token: Address| -> QuoteResponse {
+            serde_json::from_value(serde_json::json!({
+                "quote": {
+                    "sellToken": sell_token,
+                    "buyToken": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                    "receiver": null,
+                    "sellAmount": "1000000000000000000",
+                    "buyAmount": "1500000000",
+                    "validTo": 1700000000,
+                    "appData": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "feeAmount": "1000",
+                    "kind": "sell",
+                    "partiallyFillable": false,
+                    "signingScheme": "eip1271"
+                },
+                "from": "0xd8da6bf26964af9d7eed9e03e53415d37aa96045",
+                "expiration": "2023-11-14T22:13:20Z",
+                "id": 42,
+                "verified": true
+            }))
+            .unwrap()
+        };
+        let receiver = order().receiver;
+
+        let weth = Network::Mainnet.wrapped_native_token();
+        assert_eq!(
+            EthFlowOrder::from_quote(&quote(weth), &Network::Mainnet, receiver).unwrap(),
+            EthFlowOrder { sell_amount: order().sell_amount + U256::from(1_000), ..order() }
+        );
+        assert!(
+            EthFlowOrder::from_quote(&quote(Address::ZERO), &Network::Mainnet, receiver).is_err()
+        );
+        assert!(EthFlowOrder::from_quote(&quote(weth), &Network::Mainnet, Address::ZERO).is_err());
+    }
+}
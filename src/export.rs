@@ -0,0 +1,518 @@
+//! Exporting orders and trades as CSV, or Parquet with the `parquet` feature,
+//! with amounts scaled by token decimals.
+
+use std::{collections::HashMap, io::Write};
+
+use alloy::{
+    primitives::{Address, TxHash, U256},
+    providers::Provider,
+};
+use chrono::{DateTime, Utc};
+use eyre::{Result, WrapErr};
+use serde::Serialize;
+
+use crate::{
+    math::{Rounding, format_units, pro_rata},
+    models::{
+        order::{Order, OrderStatus},
+        trade::{TimedTrade, Trade},
+    },
+    primitives::order_uid::OrderUid,
+    tokens::{TokenInfo, TokenRegistry},
+};
+
+/// Type of the values of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    Text,
+    UInt,
+    Timestamp,
+}
+
+/// Column of an exported table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Column {
+    pub name: &'static str,
+    pub kind: ColumnKind,
+}
+
+const fn column(name: &'static str, kind: ColumnKind) -> Column {
+    Column { name, kind }
+}
+
+/// Value of a cell, of the kind of its column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cell {
+    Text(Option<String>),
+    UInt(Option<u64>),
+    Timestamp(Option<DateTime<Utc>>),
+}
+
+impl Cell {
+    fn text(value: impl ToString) -> Self {
+        Cell::Text(Some(value.to_string()))
+    }
+
+    /// Value as written to CSV, empty when missing.
+    fn to_csv(&self) -> String {
+        match self {
+            Cell::Text(value) => value.clone().unwrap_or_default(),
+            Cell::UInt(value) => value.map(|value| value.to_string()).unwrap_or_default(),
+            Cell::Timestamp(value) => value.map(|value| value.to_rfc3339()).unwrap_or_default(),
+        }
+    }
+}
+
+/// Row of an exported table.
+pub trait Record {
+    fn columns() -> &'static [Column];
+    /// Cells in the order of [`Record::columns`].
+    fn cells(&self) -> Vec<Cell>;
+}
+
+/// Exported trade. Amounts are in whole tokens, the fee in sell token and the
+/// surplus in the surplus token: the buy token of sell orders and the sell
+/// token of buy orders.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TradeRecord {
+    /// Block time of the trade, when exported from a [`TimedTrade`].
+    pub timestamp: Option<DateTime<Utc>>,
+    pub block_number: u64,
+    pub tx_hash: TxHash,
+    pub log_index: u64,
+    pub order_uid: OrderUid,
+    pub sell_token: Address,
+    pub sell_symbol: String,
+    pub buy_token: Address,
+    pub buy_symbol: String,
+    pub sell_amount: String,
+    pub buy_amount: String,
+    pub fee_amount: String,
+    /// Surplus, when the traded order is known.
+    pub surplus: Option<String>,
+    pub surplus_symbol: Option<String>,
+}
+
+impl TradeRecord {
+    pub fn new(trade: &Trade, order: Option<&Order>, sell: &TokenInfo, buy: &TokenInfo) -> Self {
+        let surplus = order.and_then(|order| {
+            surplus(order, trade.sell_amount, trade.buy_amount, sell.decimals, buy.decimals)
+        });
+        let surplus_symbol = order.map(|order| surplus_token(order, sell, buy).symbol.clone());
+        Self {
+            timestamp: None,
+            block_number: trade.block_number,
+            tx_hash: trade.tx_hash,
+            log_index: trade.log_index,
+            order_uid: trade.order_uid,
+            sell_token: trade.sell_token,
+            sell_symbol: sell.symbol.clone(),
+            buy_token: trade.buy_token,
+            buy_symbol: buy.symbol.clone(),
+            sell_amount: format_units(trade.sell_amount, sell.decimals),
+            buy_amount: format_units(trade.buy_amount, buy.decimals),
+            fee_amount: format_units(
+                trade.sell_amount.saturating_sub(trade.sell_amount_before_fees),
+                sell.decimals,
+            ),
+            surplus,
+            surplus_symbol,
+        }
+    }
+}
+
+const TRADE_COLUMNS: &[Column] = &[
+    column("timestamp", ColumnKind::Timestamp),
+    column("block_number", ColumnKind::UInt),
+    column("tx_hash", ColumnKind::Text),
+    column("log_index", ColumnKind::UInt),
+    column("order_uid", ColumnKind::Text),
+    column("sell_token", ColumnKind::Text),
+    column("sell_symbol", ColumnKind::Text),
+    column("buy_token", ColumnKind::Text),
+    column("buy_symbol", ColumnKind::Text),
+    column("sell_amount", ColumnKind::Text),
+    column("buy_amount", ColumnKind::Text),
+    column("fee_amount", ColumnKind::Text),
+    column("surplus", ColumnKind::Text),
+    column("surplus_symbol", ColumnKind::Text),
+];
+
+impl Record for TradeRecord {
+    fn columns() -> &'static [Column] {
+        TRADE_COLUMNS
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Timestamp(self.timestamp),
+            Cell::UInt(Some(self.block_number)),
+            Cell::text(self.tx_hash),
+            Cell::UInt(Some(self.log_index)),
+            Cell::text(self.order_uid),
+            Cell::text(self.sell_token),
+            Cell::text(&self.sell_symbol),
+            Cell::text(self.buy_token),
+            Cell::text(&self.buy_symbol),
+            Cell::text(&self.sell_amount),
+            Cell::text(&self.buy_amount),
+            Cell::text(&self.fee_amount),
+            Cell::Text(self.surplus.clone()),
+            Cell::Text(self.surplus_symbol.clone()),
+        ]
+    }
+}
+
+/// Exported order. Amounts are in whole tokens, with the surplus in the
+/// surplus token as for [`TradeRecord`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderRecord {
+    pub uid: OrderUid,
+    pub created_at: DateTime<Utc>,
+    pub valid_to: Option<DateTime<Utc>>,
+    pub owner: Address,
+    pub kind: String,
+    pub status: OrderStatus,
+    pub sell_token: Address,
+    pub sell_symbol: String,
+    pub buy_token: Address,
+    pub buy_symbol: String,
+    pub sell_amount: String,
+    pub buy_amount: String,
+    pub executed_sell_amount: String,
+    pub executed_buy_amount: String,
+    pub executed_fee: String,
+    pub surplus: Option<String>,
+    pub surplus_symbol: String,
+}
+
+impl OrderRecord {
+    pub fn new(order: &Order, sell: &TokenInfo, buy: &TokenInfo) -> Self {
+        Self {
+            uid: order.uid,
+            created_at: order.creation_date,
+            valid_to: i64::try_from(order.valid_to)
+                .ok()
+                .and_then(|valid_to| DateTime::from_timestamp(valid_to, 0)),
+            owner: order.owner,
+            kind: order.kind.clone(),
+            status: order.status,
+            sell_token: order.sell_token,
+            sell_symbol: sell.symbol.clone(),
+            buy_token: order.buy_token,
+            buy_symbol: buy.symbol.clone(),
+            sell_amount: format_units(order.sell_amount, sell.decimals),
+            buy_amount: format_units(order.buy_amount, buy.decimals),
+            executed_sell_amount: format_units(order.executed_sell_amount, sell.decimals),
+            executed_buy_amount: format_units(order.executed_buy_amount, buy.decimals),
+            executed_fee: format_units(order.executed_fee, sell.decimals),
+            surplus: surplus(
+                order,
+                order.executed_sell_amount,
+                order.executed_buy_amount,
+                sell.decimals,
+                buy.decimals,
+            ),
+            surplus_symbol: surplus_token(order, sell, buy).symbol.clone(),
+        }
+    }
+}
+
+const ORDER_COLUMNS: &[Column] = &[
+    column("uid", ColumnKind::Text),
+    column("created_at", ColumnKind::Timestamp),
+    column("valid_to", ColumnKind::Timestamp),
+    column("owner", ColumnKind::Text),
+    column("kind", ColumnKind::Text),
+    column("status", ColumnKind::Text),
+    column("sell_token", ColumnKind::Text),
+    column("sell_symbol", ColumnKind::Text),
+    column("buy_token", ColumnKind::Text),
+    column("buy_symbol", ColumnKind::Text),
+    column("sell_amount", ColumnKind::Text),
+    column("buy_amount", ColumnKind::Text),
+    column("executed_sell_amount", ColumnKind::Text),
+    column("executed_buy_amount", ColumnKind::Text),
+    column("executed_fee", ColumnKind::Text),
+    column("surplus", ColumnKind::Text),
+    column("surplus_symbol", ColumnKind::Text),
+];
+
+impl Record for OrderRecord {
+    fn columns() -> &'static [Column] {
+        ORDER_COLUMNS
+    }
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::text(self.uid),
+            Cell::Timestamp(Some(self.created_at)),
+            Cell::Timestamp(self.valid_to),
+            Cell::text(self.owner),
+            Cell::text(&self.kind),
+            Cell::text(format!("{:?}", self.status)),
+            Cell::text(self.sell_token),
+            Cell::text(&self.sell_symbol),
+            Cell::text(self.buy_token),
+            Cell::text(&self.buy_symbol),
+            Cell::text(&self.sell_amount),
+            Cell::text(&self.buy_amount),
+            Cell::text(&self.executed_sell_amount),
+            Cell::text(&self.executed_buy_amount),
+            Cell::text(&self.executed_fee),
+            Cell::Text(self.surplus.clone()),
+            Cell::text(&self.surplus_symbol),
+        ]
+    }
+}
+
+fn is_buy_order(order: &Order) -> bool {
+    order.kind == "buy"
+}
+
+/// Token the surplus of an order is paid in.
+fn surplus_token<'a>(order: &Order, sell: &'a TokenInfo, buy: &'a TokenInfo) -> &'a TokenInfo {
+    if is_buy_order(order) { sell } else { buy }
+}
+
+/// Surplus of an execution over the order's limit price, in whole surplus
+/// tokens.
+fn surplus(
+    order: &Order,
+    executed_sell: U256,
+    executed_buy: U256,
+    sell_decimals: u8,
+    buy_decimals: u8,
+) -> Option<String> {
+    if is_buy_order(order) {
+        let limit_sell =
+            pro_rata(order.sell_amount, executed_buy, order.buy_amount, Rounding::Down)?;
+        Some(format_units(limit_sell.saturating_sub(executed_sell), sell_decimals))
+    } else {
+        let limit_buy = pro_rata(order.buy_amount, executed_sell, order.sell_amount, Rounding::Up)?;
+        Some(format_units(executed_buy.saturating_sub(limit_buy), buy_decimals))
+    }
+}
+
+/// Records of `trades`, with the surplus of the trades whose order is in
+/// `orders`.
+pub async fn trade_records<'a, P: Provider>(
+    tokens: &TokenRegistry<P>,
+    trades: impl IntoIterator<Item = &'a Trade>,
+    orders: &[Order],
+) -> Result<Vec<TradeRecord>> {
+    let orders: HashMap<_, _> = orders.iter().map(|order| (order.uid, order)).collect();
+    let mut records = Vec::new();
+    for trade in trades {
+        let sell = tokens.token(trade.sell_token).await?;
+        let buy = tokens.token(trade.buy_token).await?;
+        records.push(TradeRecord::new(trade, orders.get(&trade.order_uid).copied(), &sell, &buy));
+    }
+    Ok(records)
+}
+
+/// Records of trades annotated with their block timestamps.
+pub async fn timed_trade_records<P: Provider>(
+    tokens: &TokenRegistry<P>,
+    trades: &[TimedTrade],
+    orders: &[Order],
+) -> Result<Vec<TradeRecord>> {
+    let records = trade_records(tokens, trades.iter().map(|timed| &timed.trade), orders).await?;
+    Ok(records
+        .into_iter()
+        .zip(trades)
+        .map(|(record, timed)| TradeRecord { timestamp: Some(timed.timestamp), ..record })
+        .collect())
+}
+
+/// Records of `orders`.
+pub async fn order_records<P: Provider>(
+    tokens: &TokenRegistry<P>,
+    orders: &[Order],
+) -> Result<Vec<OrderRecord>> {
+    let mut records = Vec::with_capacity(orders.len());
+    for order in orders {
+        let sell = tokens.token(order.sell_token).await?;
+        let buy = tokens.token(order.buy_token).await?;
+        records.push(OrderRecord::new(order, &sell, &buy));
+    }
+    Ok(records)
+}
+
+/// Writes `records` as CSV with a header row.
+pub fn write_csv<R: Record, W: Write>(writer: W, records: &[R]) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    writer
+        .write_record(R::columns().iter().map(|column| column.name))
+        .wrap_err("Failed to write CSV header")?;
+    for record in records {
+        writer
+            .write_record(record.cells().iter().map(Cell::to_csv))
+            .wrap_err("Failed to write CSV record")?;
+    }
+    writer.flush().wrap_err("Failed to flush CSV")?;
+    Ok(())
+}
+
+/// Writes `records` as a Parquet file with one row group.
+#[cfg(feature = "parquet")]
+pub fn write_parquet<R: Record, W: Write + Send>(writer: W, records: &[R]) -> Result<()> {
+    use std::sync::Arc;
+
+    use arrow_array::{
+        ArrayRef, RecordBatch,
+        builder::{StringBuilder, TimestampSecondBuilder, UInt64Builder},
+    };
+    use arrow_schema::{DataType, Field, Schema, TimeUnit};
+    use parquet::arrow::ArrowWriter;
+
+    let columns = R::columns();
+    let schema = Arc::new(Schema::new(
+        columns
+            .iter()
+            .map(|column| {
+                let data_type = match column.kind {
+                    ColumnKind::Text => DataType::Utf8,
+                    ColumnKind::UInt => DataType::UInt64,
+                    ColumnKind::Timestamp =>
+                        DataType::Timestamp(TimeUnit::Second, Some("UTC".into())),
+                };
+                Field::new(column.name, data_type, true)
+            })
+            .collect::<Vec<_>>(),
+    ));
+
+    let rows: Vec<Vec<Cell>> = records.iter().map(Record::cells).collect();
+    let arrays = columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| -> ArrayRef {
+            let cells = rows.iter().map(|row| &row[index]);
+            match column.kind {
+                ColumnKind::Text => {
+                    let mut builder = StringBuilder::new();
+                    for cell in cells {
+                        builder.append_option(match cell {
+                            Cell::Text(value) => value.as_deref(),
+                            _ => None,
+                        });
+                    }
+                    Arc::new(builder.finish())
+                }
+                ColumnKind::UInt => {
+                    let mut builder = UInt64Builder::new();
+                    for cell in cells {
+                        builder.append_option(match cell {
+                            Cell::UInt(value) => *value,
+                            _ => None,
+                        });
+                    }
+                    Arc::new(builder.finish())
+                }
+                ColumnKind::Timestamp => {
+                    let mut builder = TimestampSecondBuilder::new().with_timezone("UTC");
+                    for cell in cells {
+                        builder.append_option(match cell {
+                            Cell::Timestamp(value) => value.map(|value| value.timestamp()),
+                            _ => None,
+                        });
+                    }
+                    Arc::new(builder.finish())
+                }
+            }
+        })
+        .collect();
+
+    let batch = RecordBatch::try_new(schema.clone(), arrays).wrap_err("Failed to build batch")?;
+    let mut writer =
+        ArrowWriter::try_new(writer, schema, None).wrap_err("Failed to create Parquet writer")?;
+    writer.write(&batch).wrap_err("Failed to write Parquet batch")?;
+    writer.close().wrap_err("Failed to finish Parquet file")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::trade;
+
+    fn token(symbol: &str, decimals: u8) -> TokenInfo {
+        TokenInfo {
+            chain_id: 1,
+            address: Address::ZERO,
+            name: symbol.to_string(),
+            symbol: symbol.to_string(),
+            decimals,
+        }
+    }
+
+    #[test]
+    fn test_trade_record_scales_amounts() {
+        let record = TradeRecord::new(&trade(), None, &token("USDC", 6), &token("WETH", 18));
+
+        assert_eq!(record.sell_amount, "1001");
+        assert_eq!(record.buy_amount, "0.51");
+        assert_eq!(record.fee_amount, "1");
+        assert_eq!(record.surplus, None);
+    }
+
+    #[test]
+    fn test_trades_to_csv() {
+        let record = TradeRecord::new(&trade(), None, &token("USDC", 6), &token("WETH", 18));
+        let mut csv = Vec::new();
+
+        write_csv(&mut csv, &[record]).unwrap();
+
+        let csv = String::from_utf8(csv).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "timestamp,block_number,tx_hash,log_index,order_uid,sell_token,sell_symbol,buy_token,\
+             buy_symbol,sell_amount,buy_amount,fee_amount,surplus,surplus_symbol"
+        );
+        assert!(lines.next().unwrap().starts_with(",19000000,0x2222"));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[tokio::test]
+    async fn test_timed_trade_records_keep_timestamps() {
+        use alloy::providers::ProviderBuilder;
+
+        // Never called, as the tokens are cached
+        let provider = ProviderBuilder::new().on_http("http://localhost:1".parse().unwrap());
+        let tokens = TokenRegistry::new(provider, crate::config::Network::Mainnet);
+        let trade = trade();
+        tokens.insert(TokenInfo { address: trade.sell_token, ..token("USDC", 6) });
+        tokens.insert(TokenInfo { address: trade.buy_token, ..token("WETH", 18) });
+        let timestamp = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+
+        let records =
+            timed_trade_records(&tokens, &[TimedTrade { trade, timestamp }], &[]).await.unwrap();
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].timestamp, Some(timestamp));
+        assert_eq!(records[0].buy_amount, "0.51");
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_trades_to_parquet() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let record = TradeRecord::new(&trade(), None, &token("USDC", 6), &token("WETH", 18));
+        let path =
+            std::env::temp_dir().join(format!("cow-sdk-trades-{}.parquet", std::process::id()));
+
+        write_parquet(std::fs::File::create(&path).unwrap(), &[record.clone(), record]).unwrap();
+
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+        assert_eq!(
+            reader.metadata().file_metadata().schema_descr().num_columns(),
+            TradeRecord::columns().len()
+        );
+    }
+}
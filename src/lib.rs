@@ -1,10 +1,23 @@
+pub mod approval;
+pub mod blocks;
+pub mod conditional;
 pub mod config;
+pub mod contracts;
+pub mod eth_flow;
+#[cfg(feature = "export")]
+pub mod export;
+pub mod limit_orders;
+pub mod math;
 pub mod models;
 pub mod orderbook;
 mod parsing;
+pub mod permit;
 pub mod primitives;
-
-// Initialize logger
-pub fn init_logger() {
-    env_logger::Builder::from_default_env().format_timestamp(None).init();
-}
+pub mod quote;
+pub mod subgraph;
+#[cfg(feature = "opentelemetry")]
+pub mod telemetry;
+#[cfg(test)]
+mod test_utils;
+pub mod tokens;
+pub mod validation;
@@ -0,0 +1,361 @@
+//! Keeping a set of limit orders close to the market price by replacing the
+//! orders whose limit price drifted too far from it.
+
+use std::{collections::HashMap, time::Duration};
+
+use alloy::{
+    primitives::{Address, U256},
+    signers::Signer,
+};
+use eyre::{Report, Result, WrapErr, eyre};
+use tracing::{debug, info, warn};
+
+use crate::{
+    config::Network,
+    math::{Amounts, MAX_BPS, Price, Rounding, add_bps, mul_div, sub_bps},
+    models::order::OrderStatus,
+    orderbook::{
+        OrderApiClient,
+        replace::{ReplaceMode, ReplaceStep},
+    },
+    primitives::{
+        app_data::AppDataDocument,
+        order_data::{OrderData, OrderKind},
+        order_uid::OrderUid,
+    },
+};
+
+/// Scale of the native prices converted to integers, keeping the precision of
+/// the tiny per-atom prices of tokens with many decimals.
+const NATIVE_PRICE_SCALE: f64 = 1e18;
+
+/// Decides the new order of a drifting limit order.
+pub trait RepricingStrategy {
+    /// Order replacing `order`, or `None` to keep it.
+    fn reprice(&self, order: &OrderData, market_price: Price) -> Option<OrderData>;
+}
+
+/// Reprices orders whose limit price is more than `max_drift_bps` away from
+/// the market price plus `spread_bps`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DriftStrategy {
+    pub max_drift_bps: u32,
+    /// Premium over the market price asked by the orders.
+    pub spread_bps: u32,
+}
+
+impl RepricingStrategy for DriftStrategy {
+    fn reprice(&self, order: &OrderData, market_price: Price) -> Option<OrderData> {
+        // Amounts of the order at the market price plus the spread, keeping
+        // the amount the order is exact on
+        let target = match order.kind {
+            OrderKind::Sell => Amounts {
+                sell_amount: order.sell_amount,
+                buy_amount: add_bps(
+                    market_price.buy_amount(order.sell_amount, Rounding::Up)?,
+                    self.spread_bps,
+                )?,
+            },
+            OrderKind::Buy => Amounts {
+                sell_amount: mul_div(
+                    market_price.sell_amount(order.buy_amount, Rounding::Down)?,
+                    U256::from(MAX_BPS),
+                    U256::from(MAX_BPS.checked_add(self.spread_bps)?),
+                    Rounding::Down,
+                )?,
+                buy_amount: order.buy_amount,
+            },
+        };
+        let limit = Price::of(order).ok()?;
+        let lowest = Price::new(
+            target.sell_amount,
+            sub_bps(target.buy_amount, self.max_drift_bps).unwrap_or_default(),
+        )
+        .ok()?;
+        let highest =
+            Price::new(target.sell_amount, add_bps(target.buy_amount, self.max_drift_bps)?).ok()?;
+        if (lowest..=highest).contains(&limit) {
+            return None;
+        }
+
+        Some(OrderData {
+            sell_amount: target.sell_amount,
+            buy_amount: target.buy_amount,
+            ..order.clone()
+        })
+    }
+}
+
+/// Order tracked by a [`LimitOrderManager`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManagedOrder {
+    pub order: OrderData,
+    pub app_data: AppDataDocument,
+}
+
+/// Outcome of repricing a tracked order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RepriceAction {
+    /// The order is close enough to the market price.
+    Kept { uid: OrderUid },
+    /// The order is no longer open and was dropped.
+    Closed { uid: OrderUid, status: OrderStatus },
+    /// The order was replaced.
+    Replaced { old_uid: OrderUid, new_uid: OrderUid },
+    /// The order would be replaced, but the manager is in dry-run mode.
+    WouldReplace { uid: OrderUid, order: OrderData },
+    /// Repricing failed at `step` of the replacement, or before replacing
+    /// the order if `None`. The order is dropped if `dropped`, i.e. it was
+    /// cancelled without being replaced.
+    Failed { uid: OrderUid, step: Option<ReplaceStep>, error: String, dropped: bool },
+}
+
+/// Tracks limit orders of one owner and replaces them as the market moves.
+#[derive(Debug)]
+pub struct LimitOrderManager<S, R> {
+    client: OrderApiClient,
+    network: Network,
+    signer: S,
+    strategy: R,
+    mode: ReplaceMode,
+    dry_run: bool,
+    orders: HashMap<OrderUid, ManagedOrder>,
+}
+
+impl<S: Signer + Sync, R: RepricingStrategy> LimitOrderManager<S, R> {
+    pub fn new(client: OrderApiClient, network: Network, signer: S, strategy: R) -> Self {
+        Self {
+            client,
+            network,
+            signer,
+            strategy,
+            mode: ReplaceMode::default(),
+            dry_run: false,
+            orders: HashMap::new(),
+        }
+    }
+
+    /// Only reports the replacements instead of submitting them.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// How drifting orders are replaced.
+    pub fn replace_mode(mut self, mode: ReplaceMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Starts tracking a signed order.
+    pub fn track(&mut self, uid: OrderUid, order: OrderData, app_data: AppDataDocument) {
+        self.orders.insert(uid, ManagedOrder { order, app_data });
+    }
+
+    /// Stops tracking an order, without cancelling it.
+    pub fn untrack(&mut self, uid: &OrderUid) -> Option<ManagedOrder> {
+        self.orders.remove(uid)
+    }
+
+    pub fn orders(&self) -> &HashMap<OrderUid, ManagedOrder> {
+        &self.orders
+    }
+
+    /// Compares every tracked order against the market price once, replacing
+    /// the ones the strategy reprices. An order failing does not stop the
+    /// pass over the others.
+    pub async fn reprice(&mut self) -> Vec<RepriceAction> {
+        let mut prices = HashMap::new();
+        let mut actions = Vec::with_capacity(self.orders.len());
+        let uids: Vec<OrderUid> = self.orders.keys().copied().collect();
+
+        for uid in uids {
+            let status = match self.client.get_order_by_id(&uid).await {
+                Ok(order) => order.status,
+                Err(err) => {
+                    actions.push(failed_before_replacement(uid, err));
+                    continue;
+                }
+            };
+            if status.is_terminal() {
+                info!("Order {} is {:?}, no longer tracking it", uid, status);
+                self.orders.remove(&uid);
+                actions.push(RepriceAction::Closed { uid, status });
+                continue;
+            }
+
+            let managed = &self.orders[&uid];
+            let market_price = match self.market_price(&mut prices, &managed.order).await {
+                Ok(market_price) => market_price,
+                Err(err) => {
+                    actions.push(failed_before_replacement(uid, err));
+                    continue;
+                }
+            };
+            let Some(repriced) = self.strategy.reprice(&managed.order, market_price) else {
+                debug!("Order {} is within the allowed drift", uid);
+                actions.push(RepriceAction::Kept { uid });
+                continue;
+            };
+            if self.dry_run {
+                info!("Would replace order {} (dry run)", uid);
+                actions.push(RepriceAction::WouldReplace { uid, order: repriced });
+                continue;
+            }
+
+            let app_data = managed.app_data.clone();
+            let result = self
+                .client
+                .replace_order(
+                    uid,
+                    repriced.clone(),
+                    app_data.clone(),
+                    self.mode,
+                    &self.network.settlement_domain(),
+                    &self.signer,
+                )
+                .await;
+            match result {
+                Ok(replacement) => {
+                    self.orders.remove(&uid);
+                    self.orders
+                        .insert(replacement.new_uid, ManagedOrder { order: repriced, app_data });
+                    actions.push(RepriceAction::Replaced {
+                        old_uid: uid,
+                        new_uid: replacement.new_uid,
+                    });
+                }
+                Err(err) => {
+                    warn!("Failed to replace order {}: {}", uid, err);
+                    if err.old_order_cancelled {
+                        self.orders.remove(&uid);
+                    }
+                    actions.push(RepriceAction::Failed {
+                        uid,
+                        step: Some(err.step),
+                        error: format!("{:#}", err.source),
+                        dropped: err.old_order_cancelled,
+                    });
+                }
+            }
+        }
+        actions
+    }
+
+    /// Reprices the tracked orders every `interval` until none is left.
+    /// Orders that failed are retried at the next interval.
+    pub async fn run(&mut self, interval: Duration) {
+        while !self.orders.is_empty() {
+            let actions = self.reprice().await;
+            debug!("Repricing pass done: {:?}", actions);
+            tokio::time::sleep(interval).await;
+        }
+        info!("No limit orders left to manage");
+    }
+
+    /// Market price of the order's pair from the native token prices,
+    /// fetched once per pass.
+    async fn market_price(
+        &self,
+        prices: &mut HashMap<Address, f64>,
+        order: &OrderData,
+    ) -> Result<Price> {
+        let mut native_price = async |token: Address| -> Result<U256> {
+            let price = match prices.get(&token) {
+                Some(price) => *price,
+                None => {
+                    let price = self
+                        .client
+                        .get_token_price(&token)
+                        .await
+                        .wrap_err_with(|| format!("Failed to get native price of {}", token))?
+                        .price;
+                    prices.insert(token, price);
+                    price
+                }
+            };
+            U256::try_from(price * NATIVE_PRICE_SCALE)
+                .map_err(|_| eyre!("Invalid native price {} of {}", price, token))
+        };
+        let sell_price = native_price(order.sell_token).await?;
+        let buy_price = native_price(order.buy_token).await?;
+        if buy_price.is_zero() {
+            return Err(eyre!("No native price for {}", order.buy_token));
+        }
+        // Buy token atoms per sell token atom
+        Price::new(buy_price, sell_price)
+    }
+}
+
+/// Failure of an order before it could be replaced, e.g. fetching its status.
+fn failed_before_replacement(uid: OrderUid, err: Report) -> RepriceAction {
+    warn!("Failed to reprice order {}: {:#}", uid, err);
+    RepriceAction::Failed { uid, step: None, error: format!("{:#}", err), dropped: false }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::order_data;
+
+    fn price(sell_amount: u64, buy_amount: u64) -> Price {
+        Price::new(U256::from(sell_amount), U256::from(buy_amount)).unwrap()
+    }
+
+    #[test]
+    fn test_drift_strategy_keeps_order_within_threshold() {
+        let strategy = DriftStrategy { max_drift_bps: 100, spread_bps: 0 };
+
+        assert_eq!(strategy.reprice(&order_data(OrderKind::Sell, 1000, 2010), price(1, 2)), None);
+        assert_eq!(strategy.reprice(&order_data(OrderKind::Sell, 1000, 2020), price(1, 2)), None);
+        assert!(strategy.reprice(&order_data(OrderKind::Sell, 1000, 2021), price(1, 2)).is_some());
+    }
+
+    #[test]
+    fn test_drift_strategy_reprices_sell_order() {
+        let strategy = DriftStrategy { max_drift_bps: 100, spread_bps: 40 };
+
+        let repriced =
+            strategy.reprice(&order_data(OrderKind::Sell, 1000, 2000), price(2, 5)).unwrap();
+
+        assert_eq!(repriced.sell_amount, U256::from(1000));
+        assert_eq!(repriced.buy_amount, U256::from(2510));
+    }
+
+    #[test]
+    fn test_drift_strategy_reprices_buy_order() {
+        let strategy = DriftStrategy { max_drift_bps: 100, spread_bps: 0 };
+
+        let repriced =
+            strategy.reprice(&order_data(OrderKind::Buy, 1000, 2000), price(1, 4)).unwrap();
+
+        assert_eq!(repriced.sell_amount, U256::from(500));
+        assert_eq!(repriced.buy_amount, U256::from(2000));
+    }
+
+    #[test]
+    fn test_drift_strategy_keeps_amounts_beyond_f64_precision() {
+        let strategy = DriftStrategy { max_drift_bps: 0, spread_bps: 0 };
+        let sell_amount = U256::from(10).pow(U256::from(30)) + U256::from(1);
+        let order = OrderData { sell_amount, ..order_data(OrderKind::Sell, 0, 1) };
+
+        let repriced = strategy.reprice(&order, price(1, 3)).unwrap();
+
+        assert_eq!(repriced.buy_amount, sell_amount * U256::from(3));
+    }
+
+    #[test]
+    fn test_failed_before_replacement_keeps_order() {
+        let uid = OrderUid::new([0x11; 56].into());
+
+        assert_eq!(
+            failed_before_replacement(uid, eyre!("HTTP Error 500")),
+            RepriceAction::Failed {
+                uid,
+                step: None,
+                error: "HTTP Error 500".to_string(),
+                dropped: false
+            }
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! Exact arithmetic on token amounts: limit prices as ratios, basis point
+//! adjustments and decimal formatting.
+
+use std::{cmp::Ordering, fmt};
+
+use alloy::primitives::{U256, U512};
+use eyre::{Result, eyre};
+
+use crate::primitives::order_data::{OrderData, OrderKind};
+
+/// Basis points in one.
+pub const MAX_BPS: u32 = 10_000;
+
+/// Sell and buy amounts of an order, or of one stage of a quote breakdown.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct Amounts {
+    pub sell_amount: U256,
+    pub buy_amount: U256,
+}
+
+/// Direction in which a division is rounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+/// `a * b / denominator` without intermediate overflow, or `None` if the
+/// denominator is zero or the result does not fit in 256 bits.
+pub fn mul_div(a: U256, b: U256, denominator: U256, rounding: Rounding) -> Option<U256> {
+    if denominator.is_zero() {
+        return None;
+    }
+    let product = U512::from(a) * U512::from(b);
+    let denominator = U512::from(denominator);
+    let mut quotient = product / denominator;
+    if rounding == Rounding::Up && !(product % denominator).is_zero() {
+        quotient += U512::from(1);
+    }
+    U256::checked_from_limbs_slice(quotient.as_limbs())
+}
+
+/// Increases `amount` by `bps` basis points, rounding up.
+pub fn add_bps(amount: U256, bps: u32) -> Option<U256> {
+    mul_div(amount, U256::from(MAX_BPS.checked_add(bps)?), U256::from(MAX_BPS), Rounding::Up)
+}
+
+/// Decreases `amount` by `bps` basis points, rounding down, or `None` if
+/// `bps` exceeds 100%.
+pub fn sub_bps(amount: U256, bps: u32) -> Option<U256> {
+    let remaining = MAX_BPS.checked_sub(bps)?;
+    mul_div(amount, U256::from(remaining), U256::from(MAX_BPS), Rounding::Down)
+}
+
+/// Applies slippage to the amount the order is flexible on: the buy amount of
+/// sell orders decreases and the sell amount of buy orders increases.
+pub fn apply_slippage(amounts: Amounts, kind: OrderKind, slippage_bps: u32) -> Option<Amounts> {
+    match kind {
+        OrderKind::Sell =>
+            Some(Amounts { buy_amount: sub_bps(amounts.buy_amount, slippage_bps)?, ..amounts }),
+        OrderKind::Buy =>
+            Some(Amounts { sell_amount: add_bps(amounts.sell_amount, slippage_bps)?, ..amounts }),
+    }
+}
+
+/// Share of `total` that `executed` is, in basis points rounded down, capped
+/// at 100%.
+pub fn fill_ratio_bps(executed: U256, total: U256) -> Option<u32> {
+    let ratio = mul_div(executed, U256::from(MAX_BPS), total, Rounding::Down)?;
+    Some(ratio.min(U256::from(MAX_BPS)).to())
+}
+
+/// Amount of the order's other token corresponding to `executed`, out of
+/// `total`, e.g. the buy amount due for a partially executed sell amount.
+pub fn pro_rata(amount: U256, executed: U256, total: U256, rounding: Rounding) -> Option<U256> {
+    mul_div(amount, executed, total, rounding)
+}
+
+/// Exact price as the ratio of buy to sell amounts.
+#[derive(Debug, Clone, Copy)]
+pub struct Price {
+    pub sell_amount: U256,
+    pub buy_amount: U256,
+}
+
+impl Price {
+    /// Price of `buy_amount` for `sell_amount`, which must not be zero.
+    pub fn new(sell_amount: U256, buy_amount: U256) -> Result<Self> {
+        if sell_amount.is_zero() {
+            return Err(eyre!("Price with a zero sell amount"));
+        }
+        Ok(Self { sell_amount, buy_amount })
+    }
+
+    /// Limit price of an order.
+    pub fn of(order: &OrderData) -> Result<Self> {
+        Self::new(order.sell_amount, order.buy_amount)
+    }
+
+    /// Buy amount for `sell_amount` at this price.
+    pub fn buy_amount(&self, sell_amount: U256, rounding: Rounding) -> Option<U256> {
+        mul_div(sell_amount, self.buy_amount, self.sell_amount, rounding)
+    }
+
+    /// Sell amount for `buy_amount` at this price, or `None` for a zero price.
+    pub fn sell_amount(&self, buy_amount: U256, rounding: Rounding) -> Option<U256> {
+        mul_div(buy_amount, self.sell_amount, self.buy_amount, rounding)
+    }
+
+    /// Price in whole buy tokens per whole sell token, with at most
+    /// `precision` fractional digits, rounded down, or `None` if the scaled
+    /// price does not fit in 512 bits.
+    pub fn to_decimal_string(
+        &self,
+        sell_decimals: u8,
+        buy_decimals: u8,
+        precision: u8,
+    ) -> Option<String> {
+        // buy / sell * 10^sell_decimals / 10^buy_decimals, scaled by 10^precision
+        let numerator = U512::from(self.buy_amount)
+            .checked_mul(pow10(sell_decimals)?)?
+            .checked_mul(pow10(precision)?)?;
+        let denominator = U512::from(self.sell_amount).checked_mul(pow10(buy_decimals)?)?;
+        Some(format_scaled(numerator / denominator, precision))
+    }
+}
+
+impl PartialEq for Price {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Price {}
+
+impl PartialOrd for Price {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Price {
+    /// Compares the ratios by cross-multiplying, so that equivalent ratios
+    /// are equal.
+    fn cmp(&self, other: &Self) -> Ordering {
+        (U512::from(self.buy_amount) * U512::from(other.sell_amount))
+            .cmp(&(U512::from(other.buy_amount) * U512::from(self.sell_amount)))
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.buy_amount, self.sell_amount)
+    }
+}
+
+/// Formats an amount of token atoms in whole tokens, without trailing zeros.
+pub fn format_units(amount: U256, decimals: u8) -> String {
+    format_scaled(U512::from(amount), decimals)
+}
+
+/// Parses an amount of whole tokens, such as `1.5`, into token atoms.
+pub fn parse_units(value: &str, decimals: u8) -> Result<U256> {
+    let (whole, fraction) = value.trim().split_once('.').unwrap_or((value.trim(), ""));
+    if fraction.len() > usize::from(decimals) {
+        return Err(eyre!("{} has more than {} decimals", value, decimals));
+    }
+    if whole.is_empty() && fraction.is_empty() {
+        return Err(eyre!("Invalid amount {:?}", value));
+    }
+    let digits = format!("{}{:0<width$}", whole, fraction, width = usize::from(decimals));
+    if !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(eyre!("Invalid amount {:?}", value));
+    }
+    U256::from_str_radix(&digits, 10).map_err(|err| eyre!("Invalid amount {:?}: {}", value, err))
+}
+
+/// `10^exponent`, or `None` if it does not fit in 512 bits.
+fn pow10(exponent: u8) -> Option<U512> {
+    U512::from(10).checked_pow(U512::from(exponent))
+}
+
+/// Formats `value / 10^decimals`, without trailing zeros. The decimal point is
+/// placed in the digits, so that any number of decimals is formatted.
+fn format_scaled(value: U512, decimals: u8) -> String {
+    let decimals = usize::from(decimals);
+    let digits = format!("{:0>width$}", value, width = decimals + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() { whole.to_string() } else { format!("{}.{}", whole, fraction) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mul_div_does_not_overflow() {
+        let result = mul_div(U256::MAX, U256::MAX, U256::MAX, Rounding::Down);
+
+        assert_eq!(result, Some(U256::MAX));
+        assert_eq!(mul_div(U256::MAX, U256::from(2), U256::from(1), Rounding::Down), None);
+        assert_eq!(mul_div(U256::from(1), U256::from(1), U256::ZERO, Rounding::Down), None);
+    }
+
+    #[test]
+    fn test_mul_div_rounding() {
+        let (a, b, d) = (U256::from(10), U256::from(1), U256::from(3));
+
+        assert_eq!(mul_div(a, b, d, Rounding::Down), Some(U256::from(3)));
+        assert_eq!(mul_div(a, b, d, Rounding::Up), Some(U256::from(4)));
+        assert_eq!(mul_div(U256::from(9), b, d, Rounding::Up), Some(U256::from(3)));
+    }
+
+    #[test]
+    fn test_apply_slippage() {
+        let amounts = Amounts { sell_amount: U256::from(1001), buy_amount: U256::from(1001) };
+
+        let sell = apply_slippage(amounts, OrderKind::Sell, 50).unwrap();
+        let buy = apply_slippage(amounts, OrderKind::Buy, 50).unwrap();
+
+        assert_eq!(sell, Amounts { sell_amount: U256::from(1001), buy_amount: U256::from(995) });
+        assert_eq!(buy, Amounts { sell_amount: U256::from(1007), buy_amount: U256::from(1001) });
+        assert_eq!(apply_slippage(amounts, OrderKind::Sell, 10_001), None);
+    }
+
+    #[test]
+    fn test_fill_ratio_bps() {
+        assert_eq!(fill_ratio_bps(U256::from(1), U256::from(3)), Some(3333));
+        assert_eq!(fill_ratio_bps(U256::from(4), U256::from(3)), Some(10_000));
+        assert_eq!(fill_ratio_bps(U256::from(1), U256::ZERO), None);
+    }
+
+    #[test]
+    fn test_equivalent_prices_are_equal() {
+        let price = Price::new(U256::from(2), U256::from(6)).unwrap();
+
+        assert_eq!(price, Price::new(U256::from(1), U256::from(3)).unwrap());
+        assert!(price < Price::new(U256::from(1), U256::from(4)).unwrap());
+        assert_eq!(price.buy_amount(U256::from(5), Rounding::Down), Some(U256::from(15)));
+        assert!(Price::new(U256::ZERO, U256::from(1)).is_err());
+    }
+
+    #[test]
+    fn test_price_to_decimal_string() {
+        // 1000 USDC (6 decimals) for 0.5 WETH (18 decimals)
+        let price =
+            Price::new(U256::from(1_000_000_000u64), U256::from(500_000_000_000_000_000u64))
+                .unwrap();
+
+        assert_eq!(price.to_decimal_string(6, 18, 6).unwrap(), "0.0005");
+        assert_eq!(
+            Price::new(price.buy_amount, price.sell_amount).unwrap().to_decimal_string(18, 6, 2),
+            Some("2000".to_string())
+        );
+    }
+
+    #[test]
+    fn test_price_to_decimal_string_overflow() {
+        let price = Price::new(U256::from(1), U256::MAX).unwrap();
+
+        assert_eq!(price.to_decimal_string(255, 0, 0), None);
+        assert_eq!(price.to_decimal_string(0, 0, 255), None);
+        assert!(price.to_decimal_string(18, 0, 18).is_some());
+    }
+
+    #[test]
+    fn test_format_and_parse_units() {
+        assert_eq!(format_units(U256::from(1_500_000), 6), "1.5");
+        assert_eq!(format_units(U256::from(42), 0), "42");
+        assert_eq!(format_units(U256::from(42), 3), "0.042");
+        assert_eq!(format_units(U256::from(1), 255), format!("0.{}1", "0".repeat(254)));
+        assert_eq!(parse_units("1.5", 6).unwrap(), U256::from(1_500_000));
+        assert_eq!(parse_units(".25", 2).unwrap(), U256::from(25));
+        assert!(parse_units("1.1234567", 6).is_err());
+        assert!(parse_units("1.5e3", 6).is_err());
+    }
+}
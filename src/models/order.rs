@@ -1,9 +1,18 @@
-use alloy::primitives::{Address, U256};
+use alloy::{
+    primitives::{Address, Bytes, PrimitiveSignature, U256, keccak256},
+    signers::Signer,
+    sol_types::Eip712Domain,
+};
 use chrono::{DateTime, Utc};
+use eyre::{Result, WrapErr};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::primitives::{app_data::AppDataHash, order_uid::OrderUid};
+use crate::primitives::{
+    app_data::AppDataHash,
+    order_data::{BuyTokenDestination, OrderData, OrderKind, SellTokenSource, cancellation_digest},
+    order_uid::OrderUid,
+};
 
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -71,7 +80,7 @@ pub struct ExecutedAmounts {
     pub buy: U256,
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum OrderStatus {
     Fulfilled,
@@ -81,6 +90,13 @@ pub enum OrderStatus {
     PresignaturePending,
 }
 
+impl OrderStatus {
+    /// Whether the order can no longer change.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, OrderStatus::Fulfilled | OrderStatus::Expired | OrderStatus::Cancelled)
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Interactions {
@@ -96,6 +112,25 @@ pub struct Interaction {
     pub value: String,
 }
 
+impl Interaction {
+    /// Parses the interaction fields into alloy types.
+    pub fn parse(&self) -> Result<InteractionData> {
+        Ok(InteractionData {
+            target: self.target.parse().wrap_err("Failed to parse interaction target")?,
+            value: self.value.parse().wrap_err("Failed to parse interaction value")?,
+            call_data: self.call_data.parse().wrap_err("Failed to parse interaction call data")?,
+        })
+    }
+}
+
+/// Typed representation of an [`Interaction`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InteractionData {
+    pub target: Address,
+    pub value: U256,
+    pub call_data: Bytes,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Quote {
@@ -126,6 +161,89 @@ pub struct OrderCancellations {
     pub signing_scheme: String,
 }
 
+impl OrderCancellations {
+    /// Signs the cancellation of `order_ids` with the EIP-712 scheme.
+    pub async fn sign<S: Signer + Sync>(
+        order_ids: Vec<OrderUid>,
+        domain: &Eip712Domain,
+        signer: &S,
+    ) -> Result<Self> {
+        let signature = signer
+            .sign_hash(&cancellation_digest(&order_ids, domain))
+            .await
+            .wrap_err("Failed to sign order cancellations")?;
+        Ok(Self {
+            order_ids,
+            signature: Bytes::from(signature.as_bytes()).to_string(),
+            signing_scheme: "eip712".to_string(),
+        })
+    }
+}
+
+/// Signed order posted to the orderbook.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderCreation {
+    pub sell_token: Address,
+    pub buy_token: Address,
+    pub receiver: Option<Address>,
+    pub sell_amount: U256,
+    pub buy_amount: U256,
+    pub valid_to: u32,
+    pub fee_amount: U256,
+    pub kind: OrderKind,
+    pub partially_fillable: bool,
+    pub sell_token_balance: SellTokenSource,
+    pub buy_token_balance: BuyTokenDestination,
+    pub signing_scheme: String,
+    pub signature: Bytes,
+    pub from: Option<Address>,
+    /// Either the full app data JSON or its hash.
+    pub app_data: String,
+    /// Hash of the app data, set when `app_data` is the full JSON.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_data_hash: Option<AppDataHash>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote_id: Option<i64>,
+}
+
+impl OrderCreation {
+    /// Order signed by `from` with the EIP-712 scheme.
+    pub fn new(order: &OrderData, signature: &PrimitiveSignature, from: Address) -> Self {
+        Self {
+            sell_token: order.sell_token,
+            buy_token: order.buy_token,
+            receiver: (!order.receiver.is_zero()).then_some(order.receiver),
+            sell_amount: order.sell_amount,
+            buy_amount: order.buy_amount,
+            valid_to: order.valid_to,
+            fee_amount: order.fee_amount,
+            kind: order.kind,
+            partially_fillable: order.partially_fillable,
+            sell_token_balance: order.sell_token_balance,
+            buy_token_balance: order.buy_token_balance,
+            signing_scheme: "eip712".to_string(),
+            signature: signature.as_bytes().into(),
+            from: Some(from),
+            app_data: order.app_data.to_string(),
+            app_data_hash: None,
+            quote_id: None,
+        }
+    }
+
+    /// Sends the full app data document, whose hash the order signed.
+    pub fn full_app_data(mut self, app_data: String) -> Self {
+        self.app_data_hash = Some(AppDataHash(keccak256(&app_data).0));
+        self.app_data = app_data;
+        self
+    }
+
+    pub fn quote_id(mut self, quote_id: i64) -> Self {
+        self.quote_id = Some(quote_id);
+        self
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PartialOrder {
@@ -1,8 +1,16 @@
-use alloy::primitives::{Address, TxHash, U256};
+use alloy::primitives::{Address, TxHash, U256, keccak256};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
 
-use crate::models::order::{CompetitionOrderStatus, SolutionInclusion};
+use crate::{
+    models::order::{CompetitionOrderStatus, SolutionInclusion},
+    primitives::{
+        app_data::AppDataHash,
+        order_data::{BuyTokenDestination, OrderData, OrderKind, SellTokenSource},
+        order_uid::OrderUid,
+    },
+};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CompetitionOrderStatusResponse {
@@ -40,12 +48,83 @@ pub struct AppDataResponse {
     pub full_app_data: String,
 }
 
+/// Diagnostics of an order, for deployments exposing them.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderDebugResponse {
+    pub order_uid: OrderUid,
+    /// Lifecycle events of the order, oldest first.
+    #[serde(default)]
+    pub events: Vec<OrderEvent>,
+    /// Further diagnostics, which vary between API versions.
+    #[serde(flatten)]
+    pub details: Map<String, Value>,
+}
+
+/// A lifecycle event of an order, e.g. `created` or `traded`.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderEvent {
+    pub label: String,
+    pub timestamp: DateTime<Utc>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct QuoteResponse {
-    pub quote: Value,
+    pub quote: OrderQuote,
     pub from: Address,
     pub expiration: String, // TODO: change to a DateTime type (e.g. chrono::DateTime)
     pub id: i64,
     pub verified: bool,
 }
+
+/// Order parameters returned by the quote endpoint.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderQuote {
+    pub sell_token: Address,
+    pub buy_token: Address,
+    pub receiver: Option<Address>,
+    pub sell_amount: U256,
+    pub buy_amount: U256,
+    pub valid_to: u32,
+    /// Either the full app data JSON or its hash.
+    pub app_data: String,
+    pub app_data_hash: Option<AppDataHash>,
+    pub fee_amount: U256,
+    pub kind: OrderKind,
+    pub partially_fillable: bool,
+    #[serde(default)]
+    pub sell_token_balance: SellTokenSource,
+    #[serde(default)]
+    pub buy_token_balance: BuyTokenDestination,
+    pub signing_scheme: String,
+}
+
+impl OrderQuote {
+    /// Hash of the quoted app data.
+    pub fn app_data_hash(&self) -> AppDataHash {
+        self.app_data_hash
+            .or_else(|| self.app_data.parse().ok())
+            .unwrap_or_else(|| AppDataHash(keccak256(self.app_data.as_bytes()).0))
+    }
+
+    /// The quoted order, ready to be signed.
+    pub fn order_data(&self) -> OrderData {
+        OrderData {
+            sell_token: self.sell_token,
+            buy_token: self.buy_token,
+            receiver: self.receiver.unwrap_or_default(),
+            sell_amount: self.sell_amount,
+            buy_amount: self.buy_amount,
+            valid_to: self.valid_to,
+            app_data: self.app_data_hash(),
+            fee_amount: self.fee_amount,
+            kind: self.kind,
+            partially_fillable: self.partially_fillable,
+            sell_token_balance: self.sell_token_balance,
+            buy_token_balance: self.buy_token_balance,
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use alloy::primitives::{Address, TxHash, U256};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -18,3 +19,12 @@ pub struct Trade {
     pub tx_hash: TxHash,
     pub executed_protocol_fees: Vec<Value>, // TODO: create a type for this
 }
+
+/// Trade with the time of the block it was settled in.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimedTrade {
+    #[serde(flatten)]
+    pub trade: Trade,
+    pub timestamp: DateTime<Utc>,
+}
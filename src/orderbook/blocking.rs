@@ -0,0 +1,441 @@
+//! Synchronous client for the Order API, for callers that cannot run an async
+//! runtime.
+
+use std::{
+    sync::Arc,
+    thread,
+    time::{Instant, SystemTime},
+};
+
+use alloy::primitives::{Address, TxHash};
+use eyre::{Error, Result, WrapErr};
+use reqwest::{
+    Method, StatusCode,
+    blocking::{Client, Response},
+};
+use reqwest_retry::{RetryDecision, RetryPolicy, policies::ExponentialBackoff};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tracing::{debug, error, field, info, info_span, trace, warn};
+
+use super::{
+    ApiError, GetTradesQuery,
+    metrics::RequestMetrics,
+    rate_limit::{MAX_RATE_LIMITED_RETRIES, RateLimit, ThrottleMetrics, TokenBucket, retry_delay},
+    url::OrderApiUrl,
+};
+use crate::{
+    config::Network,
+    models::{
+        order::{Order, OrderCancellations, OrderCreation, PartialOrder},
+        response::{
+            AppDataResponse, CompetitionOrderStatusResponse, OrderDebugResponse, QuoteResponse,
+            SolverCompetitionResponse, TokenPriceResponse, TotalSurplusResponse,
+        },
+        trade::Trade,
+    },
+    parsing::parse_response_body,
+    primitives::{
+        app_data::{AppData, AppDataHash},
+        order_uid::OrderUid,
+    },
+};
+
+/// Blocking client for the Order API, with the same methods as
+/// [`super::OrderApiClient`].
+#[derive(Debug)]
+pub struct OrderApiClient {
+    client: Client,
+    network: Network,
+    api_url: OrderApiUrl,
+    retry_policy: ExponentialBackoff,
+    rate_limiter: Option<TokenBucket>,
+    metrics: Arc<ThrottleMetrics>,
+    request_metrics: Arc<RequestMetrics>,
+}
+
+/// Whether a response status is worth retrying, as in the async client.
+/// Rate-limited responses are retried after their `Retry-After` instead.
+fn is_transient(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::REQUEST_TIMEOUT
+}
+
+impl OrderApiClient {
+    pub fn new(network: Network) -> Result<Self> {
+        info!("Creating new blocking OrderApiClient for network: {:?}", network);
+        let api_url = OrderApiUrl::new(network.api_url())?;
+        let retry_policy = ExponentialBackoff::builder().build_with_max_retries(3);
+        Ok(Self {
+            client: Client::new(),
+            network,
+            api_url,
+            retry_policy,
+            rate_limiter: None,
+            metrics: Arc::default(),
+            request_metrics: Arc::new(RequestMetrics::new(network)),
+        })
+    }
+
+    /// Creates a client sending at most `rate_limit` requests.
+    pub fn with_rate_limit(network: Network, rate_limit: RateLimit) -> Result<Self> {
+        let client = Self::new(network)?;
+        Ok(Self { rate_limiter: Some(TokenBucket::new(rate_limit)), ..client })
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// Delays spent throttling requests of this client.
+    pub fn throttle_metrics(&self) -> Arc<ThrottleMetrics> {
+        self.metrics.clone()
+    }
+
+    /// Requests sent by this client, per endpoint.
+    pub fn request_metrics(&self) -> Arc<RequestMetrics> {
+        self.request_metrics.clone()
+    }
+
+    /// Waits for the rate limiter, if any, to allow a request.
+    fn throttle(&self) {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            let delay = rate_limiter.reserve();
+            if !delay.is_zero() {
+                debug!("Rate limiter delaying request by {:?}", delay);
+                self.metrics.record_limiter_wait(delay);
+                thread::sleep(delay);
+            }
+        }
+    }
+
+    /// Helper function to send a request to the Order API, in a span recording
+    /// the `endpoint`, final status, latency and retries.
+    fn send_request(
+        &self,
+        endpoint: &'static str,
+        url: &str,
+        method: Method,
+        body: Option<String>,
+    ) -> Result<Response> {
+        let span = info_span!(
+            "order_api_request",
+            endpoint,
+            network = %self.network,
+            %method,
+            status = field::Empty,
+            latency_ms = field::Empty,
+            retries = field::Empty,
+        );
+        let start = Instant::now();
+        let mut retries = 0;
+        let result = span.in_scope(|| self.send_with_retries(url, method, body, &mut retries));
+
+        let status = result.as_ref().ok().map(Response::status);
+        let latency = start.elapsed();
+        span.record("latency_ms", latency.as_millis() as u64);
+        span.record("retries", retries);
+        if let Some(status) = status {
+            span.record("status", status.as_u16());
+        }
+        self.request_metrics.record(endpoint, status, latency, retries);
+        result
+    }
+
+    /// Sends a request, retrying transient failures and rate-limited
+    /// responses, counted in `retries`.
+    fn send_with_retries(
+        &self,
+        url: &str,
+        method: Method,
+        body: Option<String>,
+        retries: &mut u32,
+    ) -> Result<Response> {
+        trace!("Sending request to {} with method {}", url, method);
+        let start_time = SystemTime::now();
+        let mut n_past_retries = 0;
+        let mut rate_limited_retries = 0;
+
+        loop {
+            self.throttle();
+            let mut request = self.client.request(method.clone(), url);
+            if let Some(body) = &body {
+                debug!("Request body: {}", body);
+                request = request.header("Content-Type", "application/json").body(body.clone());
+            }
+
+            let result = request.send();
+            if let Ok(response) = &result
+                && response.status() == StatusCode::TOO_MANY_REQUESTS
+                && rate_limited_retries < MAX_RATE_LIMITED_RETRIES
+            {
+                let delay = retry_delay(response.headers(), rate_limited_retries);
+                warn!("Rate limited by {}, retrying in {:?}", url, delay);
+                self.metrics.record_rate_limited(delay);
+                thread::sleep(delay);
+                rate_limited_retries += 1;
+                *retries += 1;
+                continue;
+            }
+
+            let transient = match &result {
+                Ok(response) => is_transient(response.status()),
+                Err(err) => err.is_timeout() || err.is_connect(),
+            };
+            if transient
+                && let RetryDecision::Retry { execute_after } =
+                    self.retry_policy.should_retry(start_time, n_past_retries)
+            {
+                let delay = execute_after.duration_since(SystemTime::now()).unwrap_or_default();
+                warn!("Retrying request to {} in {:?}", url, delay);
+                thread::sleep(delay);
+                n_past_retries += 1;
+                *retries += 1;
+                continue;
+            }
+
+            let response =
+                result.wrap_err_with(|| format!("Failed to send request to URL: {url}"))?;
+            debug!("Received response: {:?}", response.status());
+            return Ok(response);
+        }
+    }
+
+    /// Helper function to handle a response from the Order API.
+    fn handle_response<T: DeserializeOwned>(&self, response: Response) -> Result<T, Error> {
+        let status = response.status();
+        let body_text = response.text().wrap_err("Failed to extract response body text")?;
+
+        if !status.is_success() {
+            error!("HTTP Error {}: {}", status, body_text);
+            return Err(ApiError { status, body: body_text }.into());
+        }
+
+        trace!("Response body: {}", body_text);
+        parse_response_body(&body_text)
+    }
+
+    fn get<T: DeserializeOwned>(&self, endpoint: &'static str, url: &str) -> Result<T, Error> {
+        let response = self.send_request(endpoint, url, Method::GET, None)?;
+        self.handle_response(response)
+    }
+
+    /// Get an order by its ID.
+    pub fn get_order_by_id(&self, order_id: &OrderUid) -> Result<Order, Error> {
+        self.get("get_order_by_id", &self.api_url.get_order_by_id(order_id.to_string().as_str())?)
+    }
+
+    /// Get orders by transaction hash.
+    pub fn get_orders_by_tx_hash(&self, tx_hash: &TxHash) -> Result<Vec<Order>, Error> {
+        self.get(
+            "get_orders_by_tx_hash",
+            &self.api_url.get_order_by_tx_hash(tx_hash.to_string().as_str())?,
+        )
+    }
+
+    /// Get order status by order ID.
+    pub fn get_order_status(
+        &self,
+        order_id: &OrderUid,
+    ) -> Result<CompetitionOrderStatusResponse, Error> {
+        self.get("get_order_status", &self.api_url.get_order_status(order_id.to_string().as_str())?)
+    }
+
+    /// Create an order.
+    pub fn create_order(&self, order: &Order) -> Result<(), Error> {
+        let url = self.api_url.orders()?;
+        let body = serde_json::to_string(order).wrap_err("Failed to serialize order")?;
+
+        let response = self.send_request("create_order", &url, Method::POST, Some(body))?;
+        self.handle_response(response)
+    }
+
+    /// Post a signed order, returning its UID.
+    pub fn post_order(&self, order: &OrderCreation) -> Result<OrderUid, Error> {
+        let url = self.api_url.orders()?;
+        let body = serde_json::to_string(order).wrap_err("Failed to serialize order")?;
+
+        let response = self.send_request("post_order", &url, Method::POST, Some(body))?;
+        self.handle_response(response)
+    }
+
+    /// Cancel an order.
+    pub fn cancel_order(&self, order_cancellations: &OrderCancellations) -> Result<(), Error> {
+        let url = self.api_url.orders()?;
+        let body = serde_json::to_string(order_cancellations)
+            .wrap_err("Failed to serialize order cancellations")?;
+
+        let response = self.send_request("cancel_order", &url, Method::DELETE, Some(body))?;
+        // The API confirms cancellations with a plain JSON string
+        self.handle_response::<Value>(response).map(|_| ())
+    }
+
+    /// Get orders by account.
+    pub fn get_user_orders(
+        &self,
+        address: &Address,
+        offset: Option<u32>,
+        limit: Option<u32>,
+    ) -> Result<Vec<Order>, Error> {
+        self.get(
+            "get_user_orders",
+            &self.api_url.get_user_orders(address.to_string().as_str(), offset, limit)?,
+        )
+    }
+
+    /// Get orders by account from the version 2 endpoint.
+    pub fn get_user_orders_v2(
+        &self,
+        address: &Address,
+        offset: Option<u32>,
+        limit: Option<u32>,
+    ) -> Result<Vec<Order>, Error> {
+        self.get(
+            "get_user_orders_v2",
+            &self.api_url.get_user_orders_v2(address.to_string().as_str(), offset, limit)?,
+        )
+    }
+
+    /// Get several orders by their IDs in one request. Unknown IDs are left
+    /// out of the result.
+    pub fn get_orders_by_ids(&self, order_ids: &[OrderUid]) -> Result<Vec<Order>, Error> {
+        if order_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let url = self.api_url.orders_lookup()?;
+        let body = serde_json::to_string(order_ids).wrap_err("Failed to serialize order IDs")?;
+
+        let response = self.send_request("get_orders_by_ids", &url, Method::POST, Some(body))?;
+        self.handle_response(response)
+    }
+
+    /// Get diagnostics of an order, or `None` if the API does not have them.
+    pub fn get_order_debug(
+        &self,
+        order_id: &OrderUid,
+    ) -> Result<Option<OrderDebugResponse>, Error> {
+        let url = self.api_url.get_order_debug(order_id.to_string().as_str())?;
+        let response = self.send_request("get_order_debug", &url, Method::GET, None)?;
+        match self.handle_response(response) {
+            Ok(report) => Ok(Some(report)),
+            Err(err)
+                if err.downcast_ref::<ApiError>().is_some_and(ApiError::is_order_not_found) =>
+            {
+                debug!("No diagnostics for order {}", order_id);
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Get a quote for an order.
+    pub fn get_quote(&self, partial_order: &PartialOrder) -> Result<QuoteResponse, Error> {
+        let url = self.api_url.quote()?;
+        let body =
+            serde_json::to_string(partial_order).wrap_err("Failed to serialize partial order")?;
+
+        let response = self.send_request("get_quote", &url, Method::POST, Some(body))?;
+        self.handle_response(response)
+    }
+
+    /// Get trades by owner or order ID.
+    pub fn get_trades(&self, query: &GetTradesQuery) -> Result<Vec<Trade>, Error> {
+        self.get("get_trades", &self.api_url.get_trades(query)?)
+    }
+
+    /// Get the current batch auction. Permissioned endpoint.
+    pub fn get_auction(&self) -> Result<Value, Error> {
+        self.get("get_auction", &self.api_url.get_auction()?)
+    }
+
+    /// Get a solver competition by ID
+    pub fn get_competition_by_id(
+        &self,
+        auction_id: &i64,
+    ) -> Result<SolverCompetitionResponse, Error> {
+        self.get(
+            "get_competition_by_id",
+            &self.api_url.get_solver_competition_by_id(auction_id.to_string().as_str())?,
+        )
+    }
+
+    /// Get a solver competition by transaction hash
+    pub fn get_competition_by_tx_hash(
+        &self,
+        tx_hash: &TxHash,
+    ) -> Result<SolverCompetitionResponse, Error> {
+        self.get(
+            "get_competition_by_tx_hash",
+            &self.api_url.get_solver_competition_by_tx_hash(tx_hash.to_string().as_str())?,
+        )
+    }
+
+    /// Get the latest solver competition.
+    pub fn get_latest_competition(&self) -> Result<SolverCompetitionResponse, Error> {
+        self.get("get_latest_competition", &self.api_url.get_solver_competition_latest()?)
+    }
+
+    /// Get the native price of a token.
+    pub fn get_token_price(&self, token_address: &Address) -> Result<TokenPriceResponse, Error> {
+        self.get(
+            "get_token_price",
+            &self.api_url.get_native_price(token_address.to_string().as_str())?,
+        )
+    }
+
+    /// Get the API version.
+    pub fn get_version(&self) -> Result<String, Error> {
+        let url = self.api_url.get_api_version()?;
+        let response = self.send_request("get_version", &url, Method::GET, None)?;
+
+        Ok(response.text()?)
+    }
+
+    /// Get the total surplus of a user. [UNSTABLE]
+    pub fn get_total_surplus(&self, address: &Address) -> Result<TotalSurplusResponse, Error> {
+        self.get("get_total_surplus", &self.api_url.get_user_surplus(address.to_string().as_str())?)
+    }
+
+    /// Get app data by hash.
+    pub fn get_app_data(&self, app_data_hash: &AppDataHash) -> Result<AppDataResponse, Error> {
+        self.get(
+            "get_app_data",
+            &self.api_url.app_data_by_hash(app_data_hash.to_string().as_str())?,
+        )
+    }
+
+    /// Upload app data.
+    pub fn upload_app_data(&self, app_data: &AppData) -> Result<AppDataHash, Error> {
+        let url = self.api_url.put_app_data()?;
+        let body = serde_json::to_string(&app_data).wrap_err("Failed to serialize app data")?;
+
+        let response = self.send_request("upload_app_data", &url, Method::PUT, Some(body))?;
+        self.handle_response(response)
+    }
+
+    /// Upload app data by hash.
+    pub fn upload_app_data_by_hash(
+        &self,
+        app_data_hash: &AppDataHash,
+        app_data: &AppData,
+    ) -> Result<AppDataHash, Error> {
+        let url = self.api_url.app_data_by_hash(app_data_hash.to_string().as_str())?;
+        let body = serde_json::to_string(&app_data).wrap_err("Failed to serialize app data")?;
+
+        let response =
+            self.send_request("upload_app_data_by_hash", &url, Method::PUT, Some(body))?;
+        self.handle_response(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transient_statuses() {
+        assert!(is_transient(StatusCode::BAD_GATEWAY));
+        assert!(!is_transient(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_transient(StatusCode::BAD_REQUEST));
+        assert!(!is_transient(StatusCode::NOT_FOUND));
+    }
+}
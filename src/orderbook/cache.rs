@@ -0,0 +1,346 @@
+//! Opt-in caching of idempotent Order API responses.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use alloy::primitives::keccak256;
+use eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use tracing::{debug, warn};
+
+use super::OrderApiClient;
+use crate::{
+    models::{
+        order::Order,
+        response::{AppDataResponse, SolverCompetitionResponse},
+    },
+    primitives::{app_data::AppDataHash, order_uid::OrderUid},
+};
+
+/// A cached response and when it expires, `None` meaning never.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheEntry {
+    pub value: String,
+    /// Expiry as seconds since the Unix epoch.
+    pub expires_at: Option<u64>,
+}
+
+impl CacheEntry {
+    fn new(value: String, ttl: Option<Duration>) -> Self {
+        Self { value, expires_at: ttl.map(|ttl| (now() + ttl).as_secs()) }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now().as_secs())
+    }
+}
+
+fn now() -> Duration {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+/// Storage backing a [`CachedOrderApiClient`].
+pub trait CacheStorage: Send + Sync {
+    fn get(&self, key: &str) -> Option<CacheEntry>;
+    fn insert(&self, key: &str, entry: CacheEntry);
+    fn remove(&self, key: &str);
+}
+
+/// In-memory storage evicting the least recently used entry when full.
+#[derive(Debug)]
+pub struct MemoryCache {
+    capacity: usize,
+    inner: Mutex<MemoryCacheInner>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryCacheInner {
+    /// Entries with the tick they were last used at.
+    entries: HashMap<String, (CacheEntry, u64)>,
+    tick: u64,
+}
+
+impl MemoryCache {
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity: capacity.max(1), inner: Mutex::default() }
+    }
+}
+
+impl CacheStorage for MemoryCache {
+    fn get(&self, key: &str) -> Option<CacheEntry> {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        inner.tick += 1;
+        let tick = inner.tick;
+        let (entry, last_used) = inner.entries.get_mut(key)?;
+        *last_used = tick;
+        Some(entry.clone())
+    }
+
+    fn insert(&self, key: &str, entry: CacheEntry) {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        if inner.entries.len() >= self.capacity && !inner.entries.contains_key(key) {
+            let lru = inner
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(lru) = lru {
+                inner.entries.remove(&lru);
+            }
+        }
+        inner.tick += 1;
+        let tick = inner.tick;
+        inner.entries.insert(key.to_string(), (entry, tick));
+    }
+
+    fn remove(&self, key: &str) {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner).entries.remove(key);
+    }
+}
+
+/// On-disk storage keeping one JSON file per entry in a directory.
+#[derive(Debug)]
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+impl DiskCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .wrap_err_with(|| format!("Failed to create cache directory {}", dir.display()))?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", hex::encode(keccak256(key))))
+    }
+}
+
+impl CacheStorage for DiskCache {
+    fn get(&self, key: &str) -> Option<CacheEntry> {
+        let contents = fs::read_to_string(self.path(key)).ok()?;
+        serde_json::from_str(&contents)
+            .inspect_err(|err| warn!("Ignoring corrupt cache entry for {}: {}", key, err))
+            .ok()
+    }
+
+    fn insert(&self, key: &str, entry: CacheEntry) {
+        let result = serde_json::to_string(&entry)
+            .map_err(eyre::Report::from)
+            .and_then(|contents| Ok(fs::write(self.path(key), contents)?));
+        if let Err(err) = result {
+            warn!("Failed to write cache entry for {}: {}", key, err);
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        let _ = fs::remove_file(self.path(key));
+    }
+}
+
+/// Time to live of cached responses per endpoint, `None` caching forever.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct CacheTtls {
+    /// App data by hash, immutable.
+    pub app_data: Option<Duration>,
+    /// Orders in a terminal state. Open orders are never cached.
+    pub terminal_order: Option<Duration>,
+    /// Solver competitions by auction ID.
+    pub competition: Option<Duration>,
+    pub version: Option<Duration>,
+}
+
+impl Default for CacheTtls {
+    fn default() -> Self {
+        Self {
+            app_data: None,
+            terminal_order: None,
+            competition: None,
+            version: Some(Duration::from_secs(300)),
+        }
+    }
+}
+
+/// Wraps an [`OrderApiClient`], caching the responses of idempotent
+/// endpoints. Other endpoints are reached through [`Self::client`].
+#[derive(Debug)]
+pub struct CachedOrderApiClient<S = MemoryCache> {
+    client: OrderApiClient,
+    storage: S,
+    ttls: CacheTtls,
+}
+
+impl<S: CacheStorage> CachedOrderApiClient<S> {
+    pub fn new(client: OrderApiClient, storage: S) -> Self {
+        Self { client, storage, ttls: CacheTtls::default() }
+    }
+
+    /// Overrides the default TTLs.
+    pub fn ttls(mut self, ttls: CacheTtls) -> Self {
+        self.ttls = ttls;
+        self
+    }
+
+    /// The wrapped client.
+    pub fn client(&self) -> &OrderApiClient {
+        &self.client
+    }
+
+    /// Key of `path` in the storage, which may be shared with the clients of
+    /// other networks.
+    fn key(&self, path: &str) -> String {
+        format!("{}/{}", self.client.network(), path)
+    }
+
+    fn cached<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let entry = self.storage.get(key)?;
+        if entry.is_expired() {
+            debug!("Cache entry for {} expired", key);
+            self.storage.remove(key);
+            return None;
+        }
+        debug!("Cache hit for {}", key);
+        serde_json::from_str(&entry.value).ok()
+    }
+
+    fn store<T: Serialize>(&self, key: &str, value: &T, ttl: Option<Duration>) {
+        match serde_json::to_string(value) {
+            Ok(value) => self.storage.insert(key, CacheEntry::new(value, ttl)),
+            Err(err) => warn!("Failed to cache response for {}: {}", key, err),
+        }
+    }
+
+    /// Get an order by its ID, cached once the order is in a terminal state.
+    pub async fn get_order_by_id(&self, order_id: &OrderUid) -> Result<Order> {
+        let key = self.key(&format!("order/{}", order_id));
+        if let Some(order) = self.cached(&key) {
+            return Ok(order);
+        }
+        let order = self.client.get_order_by_id(order_id).await?;
+        if order.status.is_terminal() {
+            self.store(&key, &order, self.ttls.terminal_order);
+        }
+        Ok(order)
+    }
+
+    /// Get app data by hash.
+    pub async fn get_app_data(&self, app_data_hash: &AppDataHash) -> Result<AppDataResponse> {
+        let key = self.key(&format!("app_data/{}", app_data_hash));
+        if let Some(app_data) = self.cached(&key) {
+            return Ok(app_data);
+        }
+        let app_data = self.client.get_app_data(app_data_hash).await?;
+        self.store(&key, &app_data, self.ttls.app_data);
+        Ok(app_data)
+    }
+
+    /// Get a solver competition by ID.
+    pub async fn get_competition_by_id(
+        &self,
+        auction_id: &i64,
+    ) -> Result<SolverCompetitionResponse> {
+        let key = self.key(&format!("competition/{}", auction_id));
+        if let Some(competition) = self.cached(&key) {
+            return Ok(competition);
+        }
+        let competition = self.client.get_competition_by_id(auction_id).await?;
+        self.store(&key, &competition, self.ttls.competition);
+        Ok(competition)
+    }
+
+    /// Get the API version.
+    pub async fn get_version(&self) -> Result<String> {
+        let key = self.key("version");
+        if let Some(version) = self.cached(&key) {
+            return Ok(version);
+        }
+        let version = self.client.get_version().await?;
+        self.store(&key, &version, self.ttls.version);
+        Ok(version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Network;
+
+    fn entry(value: &str) -> CacheEntry {
+        CacheEntry::new(value.to_string(), None)
+    }
+
+    #[test]
+    fn test_memory_cache_evicts_least_recently_used() {
+        let cache = MemoryCache::new(2);
+        cache.insert("a", entry("1"));
+        cache.insert("b", entry("2"));
+        cache.get("a");
+
+        cache.insert("c", entry("3"));
+
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("c").is_some());
+    }
+
+    #[test]
+    fn test_memory_cache_survives_poisoned_lock() {
+        let cache = MemoryCache::new(2);
+        cache.insert("a", entry("1"));
+        let _ = std::panic::catch_unwind(|| {
+            let _inner = cache.inner.lock().unwrap();
+            panic!("poisoning the lock");
+        });
+
+        cache.insert("b", entry("2"));
+        cache.remove("a");
+
+        assert!(cache.get("a").is_none());
+        assert!(cache.get("b").is_some());
+    }
+
+    #[test]
+    fn test_expired_entry() {
+        let mut entry = CacheEntry::new("1".to_string(), Some(Duration::from_secs(60)));
+        assert!(!entry.is_expired());
+
+        entry.expires_at = Some(now().as_secs() - 1);
+        assert!(entry.is_expired());
+        assert!(!CacheEntry::new("1".to_string(), None).is_expired());
+    }
+
+    #[test]
+    fn test_keys_include_network() {
+        let mainnet = CachedOrderApiClient::new(
+            OrderApiClient::new(Network::Mainnet).unwrap(),
+            MemoryCache::new(1),
+        );
+        let staging = CachedOrderApiClient::new(
+            OrderApiClient::new(Network::MainnetStaging).unwrap(),
+            MemoryCache::new(1),
+        );
+
+        assert_eq!(mainnet.key("version"), "mainnet/version");
+        assert_ne!(mainnet.key("competition/1"), staging.key("competition/1"));
+    }
+
+    #[test]
+    fn test_disk_cache_round_trip() {
+        let dir = std::env::temp_dir().join(format!("cow-sdk-cache-{}", std::process::id()));
+        let cache = DiskCache::new(&dir).unwrap();
+
+        cache.insert("order/0x01", entry("{}"));
+        assert_eq!(cache.get("order/0x01"), Some(entry("{}")));
+
+        cache.remove("order/0x01");
+        assert!(cache.get("order/0x01").is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,253 @@
+//! Request counters and latency histograms of the Order API clients, exposed
+//! in the Prometheus text format.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicU32, Ordering},
+    },
+    time::Duration,
+};
+
+use futures::future::BoxFuture;
+use http::Extensions;
+use reqwest::{Request, Response, StatusCode};
+use reqwest_middleware::Next;
+
+use crate::config::Network;
+
+/// Upper bounds of the latency histogram buckets, in seconds.
+pub const LATENCY_BUCKETS: [f64; 10] = [0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Requests sent by a client, per endpoint.
+#[derive(Debug)]
+pub struct RequestMetrics {
+    network: Network,
+    endpoints: Mutex<BTreeMap<&'static str, EndpointStats>>,
+}
+
+/// Requests sent to one endpoint.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EndpointStats {
+    /// Responses by HTTP status code.
+    pub responses: BTreeMap<u16, u64>,
+    /// Requests that failed without a response.
+    pub errors: u64,
+    pub retries: u64,
+    /// Requests per latency bucket, not cumulative. The last bucket counts
+    /// the requests slower than every bound of [`LATENCY_BUCKETS`].
+    pub latency_buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    pub latency_sum: Duration,
+}
+
+impl EndpointStats {
+    pub fn requests(&self) -> u64 {
+        self.responses.values().sum::<u64>() + self.errors
+    }
+}
+
+impl RequestMetrics {
+    pub fn new(network: Network) -> Self {
+        Self { network, endpoints: Mutex::default() }
+    }
+
+    /// Records a request, with the status of its final response, if any.
+    pub(crate) fn record(
+        &self,
+        endpoint: &'static str,
+        status: Option<StatusCode>,
+        latency: Duration,
+        retries: u32,
+    ) {
+        let mut endpoints = self.endpoints.lock().unwrap_or_else(PoisonError::into_inner);
+        let stats = endpoints.entry(endpoint).or_default();
+        match status {
+            Some(status) => *stats.responses.entry(status.as_u16()).or_default() += 1,
+            None => stats.errors += 1,
+        }
+        stats.retries += u64::from(retries);
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| latency.as_secs_f64() <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        stats.latency_buckets[bucket] += 1;
+        stats.latency_sum += latency;
+    }
+
+    /// Point-in-time copy of the stats of every endpoint requested so far.
+    pub fn snapshot(&self) -> BTreeMap<&'static str, EndpointStats> {
+        self.endpoints.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// Renders the metrics in the Prometheus text exposition format.
+    pub fn render_prometheus(&self) -> String {
+        render_prometheus_all([self])
+    }
+}
+
+/// Renders the metrics of several clients in the Prometheus text exposition
+/// format, with the help and type of each metric family written once.
+pub fn render_prometheus_all<'a>(metrics: impl IntoIterator<Item = &'a RequestMetrics>) -> String {
+    let clients: Vec<(Network, BTreeMap<&'static str, EndpointStats>)> =
+        metrics.into_iter().map(|metrics| (metrics.network, metrics.snapshot())).collect();
+    let endpoints = || {
+        clients.iter().flat_map(|(network, endpoints)| {
+            endpoints.iter().map(move |(endpoint, stats)| (network, endpoint, stats))
+        })
+    };
+    let mut out = String::new();
+
+    out.push_str("# HELP cow_order_api_requests_total Order API requests by final status.\n");
+    out.push_str("# TYPE cow_order_api_requests_total counter\n");
+    for (network, endpoint, stats) in endpoints() {
+        let statuses = stats.responses.iter().map(|(status, count)| (status.to_string(), count));
+        for (status, count) in statuses.chain([("error".to_string(), &stats.errors)]) {
+            let _ = writeln!(
+                out,
+                "cow_order_api_requests_total{{network=\"{network}\",endpoint=\"{endpoint}\",\
+                 status=\"{status}\"}} {count}"
+            );
+        }
+    }
+
+    out.push_str("# HELP cow_order_api_retries_total Order API requests retried.\n");
+    out.push_str("# TYPE cow_order_api_retries_total counter\n");
+    for (network, endpoint, stats) in endpoints() {
+        let _ = writeln!(
+            out,
+            "cow_order_api_retries_total{{network=\"{network}\",endpoint=\"{endpoint}\"}} {}",
+            stats.retries
+        );
+    }
+
+    out.push_str(
+        "# HELP cow_order_api_request_duration_seconds Order API request latency, including \
+         retries.\n",
+    );
+    out.push_str("# TYPE cow_order_api_request_duration_seconds histogram\n");
+    for (network, endpoint, stats) in endpoints() {
+        let labels = format!("network=\"{network}\",endpoint=\"{endpoint}\"");
+        let mut cumulative = 0;
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(&stats.latency_buckets) {
+            cumulative += count;
+            let _ = writeln!(
+                out,
+                "cow_order_api_request_duration_seconds_bucket{{{labels},le=\"{bound}\"}} \
+                 {cumulative}"
+            );
+        }
+        let _ = writeln!(
+            out,
+            "cow_order_api_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {}",
+            stats.requests()
+        );
+        let _ = writeln!(
+            out,
+            "cow_order_api_request_duration_seconds_sum{{{labels}}} {}",
+            stats.latency_sum.as_secs_f64()
+        );
+        let _ = writeln!(
+            out,
+            "cow_order_api_request_duration_seconds_count{{{labels}}} {}",
+            stats.requests()
+        );
+    }
+    out
+}
+
+/// Attempts made to send one request, counted by [`count_attempts`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Attempts(Arc<AtomicU32>);
+
+impl Attempts {
+    /// Attempts after the first one.
+    pub(crate) fn retries(&self) -> u32 {
+        self.0.load(Ordering::Relaxed).saturating_sub(1)
+    }
+}
+
+/// Middleware counting the attempts of requests carrying [`Attempts`]. Added
+/// after the retry middleware, it sees every retry.
+pub(crate) fn count_attempts<'a>(
+    request: Request,
+    extensions: &'a mut Extensions,
+    next: Next<'a>,
+) -> BoxFuture<'a, reqwest_middleware::Result<Response>> {
+    if let Some(attempts) = extensions.get::<Attempts>() {
+        attempts.0.fetch_add(1, Ordering::Relaxed);
+    }
+    next.run(request, extensions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_buckets_latency() {
+        let metrics = RequestMetrics::new(Network::Mainnet);
+
+        metrics.record("get_order", Some(StatusCode::OK), Duration::from_millis(5), 0);
+        metrics.record("get_order", Some(StatusCode::OK), Duration::from_millis(300), 2);
+        metrics.record("get_order", None, Duration::from_secs(30), 3);
+
+        let stats = &metrics.snapshot()["get_order"];
+        assert_eq!(stats.responses[&200], 2);
+        assert_eq!(stats.errors, 1);
+        assert_eq!(stats.retries, 5);
+        assert_eq!(stats.requests(), 3);
+        assert_eq!(stats.latency_buckets[0], 1);
+        assert_eq!(stats.latency_buckets[5], 1);
+        assert_eq!(stats.latency_buckets[LATENCY_BUCKETS.len()], 1);
+    }
+
+    #[test]
+    fn test_render_prometheus() {
+        let metrics = RequestMetrics::new(Network::Mainnet);
+        metrics.record("get_quote", Some(StatusCode::OK), Duration::from_millis(40), 1);
+        metrics.record("get_quote", Some(StatusCode::BAD_REQUEST), Duration::from_millis(20), 0);
+
+        let rendered = metrics.render_prometheus();
+
+        for line in [
+            r#"cow_order_api_requests_total{network="mainnet",endpoint="get_quote",status="200"} 1"#,
+            r#"cow_order_api_requests_total{network="mainnet",endpoint="get_quote",status="400"} 1"#,
+            r#"cow_order_api_retries_total{network="mainnet",endpoint="get_quote"} 1"#,
+            r#"cow_order_api_request_duration_seconds_bucket{network="mainnet",endpoint="get_quote",le="0.025"} 1"#,
+            r#"cow_order_api_request_duration_seconds_bucket{network="mainnet",endpoint="get_quote",le="0.05"} 2"#,
+            r#"cow_order_api_request_duration_seconds_count{network="mainnet",endpoint="get_quote"} 2"#,
+        ] {
+            assert!(rendered.lines().any(|rendered| rendered == line), "missing {line}");
+        }
+    }
+
+    #[test]
+    fn test_render_prometheus_all_writes_headers_once() {
+        let mainnet = RequestMetrics::new(Network::Mainnet);
+        let gnosis = RequestMetrics::new(Network::Gnosis);
+        mainnet.record("get_order", Some(StatusCode::OK), Duration::from_millis(40), 0);
+        gnosis.record("get_order", Some(StatusCode::OK), Duration::from_millis(40), 0);
+
+        let rendered = render_prometheus_all([&mainnet, &gnosis]);
+
+        for family in [
+            "cow_order_api_requests_total",
+            "cow_order_api_retries_total",
+            "cow_order_api_request_duration_seconds",
+        ] {
+            let headers = rendered.lines().filter(|line| {
+                line.starts_with(&format!("# TYPE {family} "))
+                    || line.starts_with(&format!("# HELP {family} "))
+            });
+            assert_eq!(headers.count(), 2, "{family}");
+        }
+        for network in ["mainnet", "gnosis"] {
+            let line = format!(
+                r#"cow_order_api_requests_total{{network="{network}",endpoint="get_order",status="200"}} 1"#
+            );
+            assert!(rendered.lines().any(|rendered| rendered == line), "missing {line}");
+        }
+    }
+}
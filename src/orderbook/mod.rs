@@ -1,21 +1,36 @@
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod cache;
+pub mod metrics;
+pub mod multi_network;
+pub mod rate_limit;
+pub mod recording;
+pub mod replace;
 mod url;
 
+use std::{fmt, sync::Arc, time::Instant};
+
 use alloy::primitives::{Address, TxHash};
 use eyre::{Error, Result, WrapErr};
-use log::{debug, error, info, trace};
-use reqwest::{Client, Method, Response};
-use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
+use metrics::{Attempts, RequestMetrics, count_attempts};
+use rate_limit::{
+    MAX_RATE_LIMITED_RETRIES, RateLimit, RateLimitAwareStrategy, ThrottleMetrics, TokenBucket,
+    retry_delay,
+};
+use reqwest::{Client, Method, Response, StatusCode};
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware, Middleware};
 use reqwest_retry::{RetryTransientMiddleware, policies::ExponentialBackoff};
 use serde::de::DeserializeOwned;
 use serde_json::Value;
+use tracing::{Instrument, debug, error, field, info, info_span, trace, warn};
 use url::OrderApiUrl;
 
 use crate::{
     config::Network,
     models::{
-        order::{Order, OrderCancellations, PartialOrder},
+        order::{Order, OrderCancellations, OrderCreation, PartialOrder},
         response::{
-            AppDataResponse, CompetitionOrderStatusResponse, QuoteResponse,
+            AppDataResponse, CompetitionOrderStatusResponse, OrderDebugResponse, QuoteResponse,
             SolverCompetitionResponse, TokenPriceResponse, TotalSurplusResponse,
         },
         trade::Trade,
@@ -31,9 +46,47 @@ use crate::{
 #[derive(Debug)]
 pub struct OrderApiClient {
     client: ClientWithMiddleware,
+    network: Network,
     api_url: OrderApiUrl,
+    rate_limiter: Option<TokenBucket>,
+    metrics: Arc<ThrottleMetrics>,
+    request_metrics: Arc<RequestMetrics>,
+}
+
+/// Unsuccessful response from the Order API.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub body: String,
+}
+
+impl ApiError {
+    pub fn is_not_found(&self) -> bool {
+        self.status == StatusCode::NOT_FOUND
+    }
+
+    /// `errorType` of the API's JSON error body.
+    pub fn error_type(&self) -> Option<String> {
+        let body: Value = serde_json::from_str(&self.body).ok()?;
+        body.get("errorType")?.as_str().map(str::to_string)
+    }
+
+    /// Whether the API reported the order as unknown, unlike a `404` of a
+    /// wrong base URL or path.
+    pub fn is_order_not_found(&self) -> bool {
+        self.is_not_found()
+            && matches!(self.error_type().as_deref(), Some("NotFound" | "OrderNotFound"))
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HTTP Error {}: {}", self.status, self.body)
+    }
 }
 
+impl std::error::Error for ApiError {}
+
 /// Query to get trades by owner or order ID.
 #[derive(Debug)]
 pub enum GetTradesQuery {
@@ -43,37 +96,148 @@ pub enum GetTradesQuery {
 
 impl OrderApiClient {
     pub fn new(network: Network) -> Result<Self> {
+        Self::build(network, None)
+    }
+
+    /// Creates a client sending its requests through `middleware`, e.g. a
+    /// [`recording::Recorder`]. The middleware wraps the retries, so it only
+    /// sees the final response of each request.
+    pub fn with_middleware(network: Network, middleware: Arc<dyn Middleware>) -> Result<Self> {
+        Self::build(network, Some(middleware))
+    }
+
+    fn build(network: Network, middleware: Option<Arc<dyn Middleware>>) -> Result<Self> {
         info!("Creating new OrderApiClient for network: {:?}", network);
         let api_url = OrderApiUrl::new(network.api_url())?;
         let retry_policy = ExponentialBackoff::builder().build_with_max_retries(3);
-        let client = ClientBuilder::new(Client::new())
-            .with(RetryTransientMiddleware::new_with_policy(retry_policy))
+        let mut builder = ClientBuilder::new(Client::new());
+        if let Some(middleware) = middleware {
+            builder = builder.with_arc(middleware);
+        }
+        let client = builder
+            .with(RetryTransientMiddleware::new_with_policy_and_strategy(
+                retry_policy,
+                RateLimitAwareStrategy,
+            ))
+            .with(count_attempts)
             .build();
-        Ok(Self { client, api_url })
+        Ok(Self {
+            client,
+            network,
+            api_url,
+            rate_limiter: None,
+            metrics: Arc::default(),
+            request_metrics: Arc::new(RequestMetrics::new(network)),
+        })
     }
 
-    /// Helper function to send a request to the Order API.
+    /// Creates a client sending at most `rate_limit` requests.
+    pub fn with_rate_limit(network: Network, rate_limit: RateLimit) -> Result<Self> {
+        let client = Self::new(network)?;
+        Ok(Self { rate_limiter: Some(TokenBucket::new(rate_limit)), ..client })
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// Delays spent throttling requests of this client.
+    pub fn throttle_metrics(&self) -> Arc<ThrottleMetrics> {
+        self.metrics.clone()
+    }
+
+    /// Requests sent by this client, per endpoint.
+    pub fn request_metrics(&self) -> Arc<RequestMetrics> {
+        self.request_metrics.clone()
+    }
+
+    /// Waits for the rate limiter, if any, to allow a request.
+    async fn throttle(&self) {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            let delay = rate_limiter.reserve();
+            if !delay.is_zero() {
+                debug!("Rate limiter delaying request by {:?}", delay);
+                self.metrics.record_limiter_wait(delay);
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+
+    /// Helper function to send a request to the Order API, in a span recording
+    /// the `endpoint`, final status, latency and retries.
     async fn send_request(
         &self,
+        endpoint: &'static str,
         url: &str,
         method: Method,
         body: Option<String>,
     ) -> Result<Response, Error> {
-        trace!("Sending request to {} with method {}", url, method);
-        let mut request = self.client.request(method, url);
-
-        if let Some(body) = body {
-            debug!("Request body: {}", body);
-            request = request.header("Content-Type", "application/json").body(body);
+        let span = info_span!(
+            "order_api_request",
+            endpoint,
+            network = %self.network,
+            %method,
+            status = field::Empty,
+            latency_ms = field::Empty,
+            retries = field::Empty,
+        );
+        let start = Instant::now();
+        let attempts = Attempts::default();
+        let result =
+            self.send_with_retries(url, method, body, &attempts).instrument(span.clone()).await;
+
+        let status = result.as_ref().ok().map(Response::status);
+        let latency = start.elapsed();
+        span.record("latency_ms", latency.as_millis() as u64);
+        span.record("retries", attempts.retries());
+        if let Some(status) = status {
+            span.record("status", status.as_u16());
         }
+        self.request_metrics.record(endpoint, status, latency, attempts.retries());
+        result
+    }
 
-        let response = request
-            .send()
-            .await
-            .wrap_err_with(|| format!("Failed to send request to URL: {url}"))?;
-
-        debug!("Received response: {:?}", response.status());
-        Ok(response)
+    /// Sends a request, retrying rate-limited responses. `attempts` counts
+    /// every attempt, including the transient failures retried by the
+    /// middleware.
+    async fn send_with_retries(
+        &self,
+        url: &str,
+        method: Method,
+        body: Option<String>,
+        attempts: &Attempts,
+    ) -> Result<Response, Error> {
+        trace!("Sending request to {} with method {}", url, method);
+        let mut rate_limited_retries = 0;
+
+        loop {
+            self.throttle().await;
+            let mut request =
+                self.client.request(method.clone(), url).with_extension(attempts.clone());
+            if let Some(body) = &body {
+                debug!("Request body: {}", body);
+                request = request.header("Content-Type", "application/json").body(body.clone());
+            }
+
+            let response = request
+                .send()
+                .await
+                .wrap_err_with(|| format!("Failed to send request to URL: {url}"))?;
+
+            if response.status() == StatusCode::TOO_MANY_REQUESTS
+                && rate_limited_retries < MAX_RATE_LIMITED_RETRIES
+            {
+                let delay = retry_delay(response.headers(), rate_limited_retries);
+                warn!("Rate limited by {}, retrying in {:?}", url, delay);
+                self.metrics.record_rate_limited(delay);
+                tokio::time::sleep(delay).await;
+                rate_limited_retries += 1;
+                continue;
+            }
+
+            debug!("Received response: {:?}", response.status());
+            return Ok(response);
+        }
     }
 
     /// Helper function to handle a response from the Order API.
@@ -83,7 +247,7 @@ impl OrderApiClient {
 
         if !status.is_success() {
             error!("HTTP Error {}: {}", status, body_text);
-            return Err(eyre::eyre!("HTTP Error {}: {}", status, body_text));
+            return Err(ApiError { status, body: body_text }.into());
         }
 
         trace!("Response body: {}", body_text);
@@ -94,14 +258,14 @@ impl OrderApiClient {
     /// Get an order by its ID.
     pub async fn get_order_by_id(&self, order_id: &OrderUid) -> Result<Order, Error> {
         let url = self.api_url.get_order_by_id(order_id.to_string().as_str())?;
-        let response = self.send_request(&url, Method::GET, None).await?;
+        let response = self.send_request("get_order_by_id", &url, Method::GET, None).await?;
         self.handle_response(response).await
     }
 
     /// Get orders by transaction hash.
     pub async fn get_orders_by_tx_hash(&self, tx_hash: &TxHash) -> Result<Vec<Order>, Error> {
         let url = self.api_url.get_order_by_tx_hash(tx_hash.to_string().as_str())?;
-        let response = self.send_request(&url, Method::GET, None).await?;
+        let response = self.send_request("get_orders_by_tx_hash", &url, Method::GET, None).await?;
         self.handle_response(response).await
     }
 
@@ -111,7 +275,7 @@ impl OrderApiClient {
         order_id: &OrderUid,
     ) -> Result<CompetitionOrderStatusResponse, Error> {
         let url = self.api_url.get_order_status(order_id.to_string().as_str())?;
-        let response = self.send_request(&url, Method::GET, None).await?;
+        let response = self.send_request("get_order_status", &url, Method::GET, None).await?;
         self.handle_response(response).await
     }
 
@@ -120,7 +284,16 @@ impl OrderApiClient {
         let url = self.api_url.orders()?;
         let body = serde_json::to_string(order).wrap_err("Failed to serialize order")?;
 
-        let response = self.send_request(&url, Method::POST, Some(body)).await?;
+        let response = self.send_request("create_order", &url, Method::POST, Some(body)).await?;
+        self.handle_response(response).await
+    }
+
+    /// Post a signed order, returning its UID.
+    pub async fn post_order(&self, order: &OrderCreation) -> Result<OrderUid, Error> {
+        let url = self.api_url.orders()?;
+        let body = serde_json::to_string(order).wrap_err("Failed to serialize order")?;
+
+        let response = self.send_request("post_order", &url, Method::POST, Some(body)).await?;
         self.handle_response(response).await
     }
 
@@ -134,8 +307,9 @@ impl OrderApiClient {
         let body = serde_json::to_string(order_cancellations)
             .wrap_err("Failed to serialize order cancellations")?;
 
-        let response = self.send_request(&url, Method::DELETE, Some(body)).await?;
-        self.handle_response(response).await
+        let response = self.send_request("cancel_order", &url, Method::DELETE, Some(body)).await?;
+        // The API confirms cancellations with a plain JSON string
+        self.handle_response::<Value>(response).await.map(|_| ())
     }
 
     /// Get orders by account.
@@ -146,24 +320,69 @@ impl OrderApiClient {
         limit: Option<u32>,
     ) -> Result<Vec<Order>, Error> {
         let url = self.api_url.get_user_orders(address.to_string().as_str(), offset, limit)?;
-        let response = self.send_request(&url, Method::GET, None).await?;
+        let response = self.send_request("get_user_orders", &url, Method::GET, None).await?;
         self.handle_response(response).await
     }
 
+    /// Get orders by account from the version 2 endpoint.
+    pub async fn get_user_orders_v2(
+        &self,
+        address: &Address,
+        offset: Option<u32>,
+        limit: Option<u32>,
+    ) -> Result<Vec<Order>, Error> {
+        let url = self.api_url.get_user_orders_v2(address.to_string().as_str(), offset, limit)?;
+        let response = self.send_request("get_user_orders_v2", &url, Method::GET, None).await?;
+        self.handle_response(response).await
+    }
+
+    /// Get several orders by their IDs in one request. Unknown IDs are left
+    /// out of the result.
+    pub async fn get_orders_by_ids(&self, order_ids: &[OrderUid]) -> Result<Vec<Order>, Error> {
+        if order_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let url = self.api_url.orders_lookup()?;
+        let body = serde_json::to_string(order_ids).wrap_err("Failed to serialize order IDs")?;
+
+        let response =
+            self.send_request("get_orders_by_ids", &url, Method::POST, Some(body)).await?;
+        self.handle_response(response).await
+    }
+
+    /// Get diagnostics of an order, or `None` if the API does not have them.
+    pub async fn get_order_debug(
+        &self,
+        order_id: &OrderUid,
+    ) -> Result<Option<OrderDebugResponse>, Error> {
+        let url = self.api_url.get_order_debug(order_id.to_string().as_str())?;
+        let response = self.send_request("get_order_debug", &url, Method::GET, None).await?;
+        match self.handle_response(response).await {
+            Ok(report) => Ok(Some(report)),
+            Err(err)
+                if err.downcast_ref::<ApiError>().is_some_and(ApiError::is_order_not_found) =>
+            {
+                debug!("No diagnostics for order {}", order_id);
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
     /// Get a quote for an order.
     pub async fn get_quote(&self, partial_order: &PartialOrder) -> Result<QuoteResponse, Error> {
         let url = self.api_url.quote()?;
         let body =
             serde_json::to_string(partial_order).wrap_err("Failed to serialize partial order")?;
 
-        let response = self.send_request(&url, Method::POST, Some(body)).await?;
+        let response = self.send_request("get_quote", &url, Method::POST, Some(body)).await?;
         self.handle_response(response).await
     }
 
     /// Get trades by owner or order ID.
     pub async fn get_trades(&self, query: &GetTradesQuery) -> Result<Vec<Trade>, Error> {
         let url = self.api_url.get_trades(query)?;
-        let response = self.send_request(&url, Method::GET, None).await?;
+        let response = self.send_request("get_trades", &url, Method::GET, None).await?;
         self.handle_response(response).await
     }
 
@@ -171,7 +390,7 @@ impl OrderApiClient {
     // TODO: get permission and implement struct
     pub async fn get_auction(&self) -> Result<Value, Error> {
         let url = self.api_url.get_auction()?;
-        let response = self.send_request(&url, Method::GET, None).await?;
+        let response = self.send_request("get_auction", &url, Method::GET, None).await?;
         self.handle_response(response).await
     }
 
@@ -181,7 +400,7 @@ impl OrderApiClient {
         auction_id: &i64,
     ) -> Result<SolverCompetitionResponse, Error> {
         let url = self.api_url.get_solver_competition_by_id(auction_id.to_string().as_str())?;
-        let response = self.send_request(&url, Method::GET, None).await?;
+        let response = self.send_request("get_competition_by_id", &url, Method::GET, None).await?;
         self.handle_response(response).await
     }
 
@@ -191,14 +410,15 @@ impl OrderApiClient {
         tx_hash: &TxHash,
     ) -> Result<SolverCompetitionResponse, Error> {
         let url = self.api_url.get_solver_competition_by_tx_hash(tx_hash.to_string().as_str())?;
-        let response = self.send_request(&url, Method::GET, None).await?;
+        let response =
+            self.send_request("get_competition_by_tx_hash", &url, Method::GET, None).await?;
         self.handle_response(response).await
     }
 
     /// Get the latest solver competition.
     pub async fn get_latest_competition(&self) -> Result<SolverCompetitionResponse, Error> {
         let url = self.api_url.get_solver_competition_latest()?;
-        let response = self.send_request(&url, Method::GET, None).await?;
+        let response = self.send_request("get_latest_competition", &url, Method::GET, None).await?;
         self.handle_response(response).await
     }
 
@@ -208,14 +428,14 @@ impl OrderApiClient {
         token_address: &Address,
     ) -> Result<TokenPriceResponse, Error> {
         let url = self.api_url.get_native_price(token_address.to_string().as_str())?;
-        let response = self.send_request(&url, Method::GET, None).await?;
+        let response = self.send_request("get_token_price", &url, Method::GET, None).await?;
         self.handle_response(response).await
     }
 
     /// Get the API version.
     pub async fn get_version(&self) -> Result<String, Error> {
         let url = self.api_url.get_api_version()?;
-        let response = self.send_request(&url, Method::GET, None).await?;
+        let response = self.send_request("get_version", &url, Method::GET, None).await?;
 
         Ok(response.text().await?)
     }
@@ -226,7 +446,7 @@ impl OrderApiClient {
         address: &Address,
     ) -> Result<TotalSurplusResponse, Error> {
         let url = self.api_url.get_user_surplus(address.to_string().as_str())?;
-        let response = self.send_request(&url, Method::GET, None).await?;
+        let response = self.send_request("get_total_surplus", &url, Method::GET, None).await?;
         self.handle_response(response).await
     }
 
@@ -237,7 +457,7 @@ impl OrderApiClient {
     ) -> Result<AppDataResponse, Error> {
         let app_data_hash_str = app_data_hash.to_string();
         let url = self.api_url.app_data_by_hash(app_data_hash_str.as_str())?;
-        let response = self.send_request(&url, Method::GET, None).await?;
+        let response = self.send_request("get_app_data", &url, Method::GET, None).await?;
         self.handle_response(response).await
     }
 
@@ -246,7 +466,7 @@ impl OrderApiClient {
         let url = self.api_url.put_app_data()?;
         let body = serde_json::to_string(&app_data).wrap_err("Failed to serialize app data")?;
 
-        let response = self.send_request(&url, Method::PUT, Some(body)).await?;
+        let response = self.send_request("upload_app_data", &url, Method::PUT, Some(body)).await?;
 
         self.handle_response(response).await
     }
@@ -261,8 +481,28 @@ impl OrderApiClient {
         let url = self.api_url.app_data_by_hash(app_data_hash_str.as_str())?;
         let body = serde_json::to_string(&app_data).wrap_err("Failed to serialize app data")?;
 
-        let response = self.send_request(&url, Method::PUT, Some(body)).await?;
+        let response =
+            self.send_request("upload_app_data_by_hash", &url, Method::PUT, Some(body)).await?;
 
         self.handle_response(response).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_not_found_requires_api_error_body() {
+        let error = |status, body: &str| ApiError { status, body: body.to_string() };
+        let not_found = r#"{"errorType":"NotFound","description":"Order was not found"}"#;
+
+        assert!(error(StatusCode::NOT_FOUND, not_found).is_order_not_found());
+        assert!(!error(StatusCode::NOT_FOUND, "404 page not found").is_order_not_found());
+        assert!(!error(StatusCode::BAD_REQUEST, not_found).is_order_not_found());
+        assert_eq!(
+            error(StatusCode::NOT_FOUND, not_found).error_type().as_deref(),
+            Some("NotFound")
+        );
+    }
+}
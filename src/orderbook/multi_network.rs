@@ -0,0 +1,326 @@
+//! Order API clients for several networks at once.
+
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use alloy::primitives::Address;
+use eyre::{Result, WrapErr, eyre};
+use futures::future::join_all;
+
+use super::{
+    ApiError, GetTradesQuery, OrderApiClient, metrics::render_prometheus_all, rate_limit::RateLimit,
+};
+use crate::{
+    config::Network,
+    models::{order::Order, trade::Trade},
+    primitives::order_uid::OrderUid,
+};
+
+/// Most orders requested per page when fetching every order of an owner.
+const USER_ORDERS_PAGE_SIZE: u32 = 1000;
+
+/// Lazily created [`OrderApiClient`]s for a set of networks.
+#[derive(Debug)]
+pub struct MultiNetworkClient {
+    networks: Vec<Network>,
+    rate_limit: Option<RateLimit>,
+    clients: Mutex<HashMap<Network, Arc<OrderApiClient>>>,
+}
+
+impl MultiNetworkClient {
+    pub fn new(networks: impl IntoIterator<Item = Network>) -> Self {
+        let mut unique = Vec::new();
+        for network in networks {
+            if !unique.contains(&network) {
+                unique.push(network);
+            }
+        }
+        Self { networks: unique, rate_limit: None, clients: Mutex::default() }
+    }
+
+    /// Clients for the production and staging (barn) variants of `network`.
+    pub fn environments(network: Network) -> Self {
+        Self::new([network.prod()].into_iter().chain(network.staging()))
+    }
+
+    /// Rate limits each network's client separately.
+    pub fn with_rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+
+    pub fn networks(&self) -> &[Network] {
+        &self.networks
+    }
+
+    /// The client of `network`, created on first use.
+    pub fn client(&self, network: Network) -> Result<Arc<OrderApiClient>> {
+        if !self.networks.contains(&network) {
+            return Err(eyre!("Network {} is not configured", network));
+        }
+        let mut clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(client) = clients.get(&network) {
+            return Ok(client.clone());
+        }
+        let client = Arc::new(match self.rate_limit {
+            Some(rate_limit) => OrderApiClient::with_rate_limit(network, rate_limit)?,
+            None => OrderApiClient::new(network)?,
+        });
+        clients.insert(network, client.clone());
+        Ok(client)
+    }
+
+    /// The client of the configured network with `chain_id`, preferring the
+    /// production orderbook when its staging one is configured too.
+    pub fn client_for_chain(&self, chain_id: u64) -> Result<Arc<OrderApiClient>> {
+        let mut networks = self.networks.iter().filter(|network| network.chain_id() == chain_id);
+        let first =
+            networks.next().ok_or_else(|| eyre!("No network configured for chain {}", chain_id))?;
+        let network = std::iter::once(first)
+            .chain(networks)
+            .find(|network| network.prod() == **network)
+            .unwrap_or(first);
+        self.client(*network)
+    }
+
+    /// Renders the request metrics of the clients created so far in the
+    /// Prometheus text exposition format.
+    pub fn render_metrics(&self) -> String {
+        let clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
+        let metrics: Vec<_> = self
+            .networks
+            .iter()
+            .filter_map(|network| Some(clients.get(network)?.request_metrics()))
+            .collect();
+        render_prometheus_all(metrics.iter().map(Arc::as_ref))
+    }
+
+    /// Runs `query` against every network concurrently, returning each
+    /// network's result.
+    pub async fn fan_out<T, F, Fut>(&self, query: F) -> Vec<(Network, Result<T>)>
+    where
+        F: Fn(Network, Arc<OrderApiClient>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let queries = self.networks.iter().map(|&network| {
+            let client = self.client(network);
+            let query = &query;
+            async move {
+                let result = match client {
+                    Ok(client) => query(network, client).await,
+                    Err(err) => Err(err),
+                };
+                (network, result)
+            }
+        });
+        join_all(queries).await
+    }
+
+    /// All orders of `owner` on every network, newest first, fetched page by
+    /// page. Fails if any network fails.
+    pub async fn get_user_orders(&self, owner: &Address) -> Result<Vec<(Network, Order)>> {
+        let results = self
+            .fan_out(|_, client| async move {
+                paginate(USER_ORDERS_PAGE_SIZE, async |offset, limit| {
+                    client.get_user_orders(owner, Some(offset), Some(limit)).await
+                })
+                .await
+            })
+            .await;
+        let mut orders = merge(results)?;
+        orders.sort_by_key(|(_, order)| Reverse(order.creation_date));
+        Ok(orders)
+    }
+
+    /// Trades of `owner` on every network, newest first. Fails if any network
+    /// fails.
+    pub async fn get_trades(&self, owner: &Address) -> Result<Vec<(Network, Trade)>> {
+        let query = GetTradesQuery::ByOwner(*owner);
+        let query = &query;
+        let results = self.fan_out(|_, client| async move { client.get_trades(query).await }).await;
+        let mut trades = merge(results)?;
+        trades.sort_by_key(|(_, trade)| Reverse(trade.block_number));
+        Ok(trades)
+    }
+
+    /// Finds the network an order was placed on, e.g. to tell whether it lives
+    /// in the production or staging orderbook.
+    pub async fn find_order(&self, order_id: &OrderUid) -> Result<Option<(Network, Order)>> {
+        let results = self
+            .fan_out(|_, client| async move {
+                match client.get_order_by_id(order_id).await {
+                    Ok(order) => Ok(Some(order)),
+                    Err(err)
+                        if err
+                            .downcast_ref::<ApiError>()
+                            .is_some_and(ApiError::is_order_not_found) =>
+                        Ok(None),
+                    Err(err) => Err(err),
+                }
+            })
+            .await;
+        find_first(results)
+    }
+
+    /// Finds the network the trades of an order were indexed on.
+    pub async fn find_trades(&self, order_id: &OrderUid) -> Result<Option<(Network, Vec<Trade>)>> {
+        let query = GetTradesQuery::ByOrderId(*order_id);
+        let query = &query;
+        let results = self
+            .fan_out(|_, client| async move {
+                let trades = client.get_trades(query).await?;
+                Ok((!trades.is_empty()).then_some(trades))
+            })
+            .await;
+        find_first(results)
+    }
+}
+
+/// First value found by a lookup on several networks. Errors are only
+/// reported when no network has the value.
+fn find_first<T>(results: Vec<(Network, Result<Option<T>>)>) -> Result<Option<(Network, T)>> {
+    let mut error = None;
+    for (network, result) in results {
+        match result {
+            Ok(Some(value)) => return Ok(Some((network, value))),
+            Ok(None) => {}
+            Err(err) => {
+                error.get_or_insert(err.wrap_err(format!("Lookup failed on {}", network)));
+            }
+        }
+    }
+    error.map_or(Ok(None), Err)
+}
+
+/// Fetches pages of `page_size` items from `fetch(offset, limit)` until one
+/// comes back short.
+async fn paginate<T>(
+    page_size: u32,
+    mut fetch: impl AsyncFnMut(u32, u32) -> Result<Vec<T>>,
+) -> Result<Vec<T>> {
+    let mut items = Vec::new();
+    loop {
+        let offset = u32::try_from(items.len()).wrap_err("Too many items to paginate")?;
+        let page = fetch(offset, page_size).await?;
+        let last = page.len() < page_size as usize;
+        items.extend(page);
+        if last {
+            return Ok(items);
+        }
+    }
+}
+
+/// Flattens the per-network results of a fan-out query.
+fn merge<T>(results: Vec<(Network, Result<Vec<T>>)>) -> Result<Vec<(Network, T)>> {
+    let mut merged = Vec::new();
+    for (network, result) in results {
+        let values = result.wrap_err_with(|| format!("Query failed on {}", network))?;
+        merged.extend(values.into_iter().map(|value| (network, value)));
+    }
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clients_are_created_once_per_network() {
+        let client = MultiNetworkClient::new([Network::Mainnet, Network::Gnosis, Network::Mainnet]);
+
+        assert_eq!(client.networks(), &[Network::Mainnet, Network::Gnosis]);
+        assert!(Arc::ptr_eq(
+            &client.client(Network::Gnosis).unwrap(),
+            &client.client_for_chain(100).unwrap()
+        ));
+        assert!(client.client(Network::Base).is_err());
+        assert!(client.client_for_chain(8453).is_err());
+    }
+
+    #[test]
+    fn test_client_for_chain_prefers_prod() {
+        let client = MultiNetworkClient::new([Network::GnosisStaging, Network::Gnosis]);
+
+        assert!(Arc::ptr_eq(
+            &client.client(Network::Gnosis).unwrap(),
+            &client.client_for_chain(100).unwrap()
+        ));
+        let staging = MultiNetworkClient::new([Network::GnosisStaging]);
+        assert_eq!(staging.client_for_chain(100).unwrap().network(), Network::GnosisStaging);
+    }
+
+    #[tokio::test]
+    async fn test_paginate_until_short_page() {
+        let items: Vec<u32> = (0..5).collect();
+        let mut requests = Vec::new();
+
+        let fetched = paginate(2, async |offset, limit| {
+            requests.push((offset, limit));
+            let start = (offset as usize).min(items.len());
+            let end = (start + limit as usize).min(items.len());
+            Ok(items[start..end].to_vec())
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(fetched, items);
+        assert_eq!(requests, vec![(0, 2), (2, 2), (4, 2)]);
+    }
+
+    #[test]
+    fn test_environments() {
+        let client = MultiNetworkClient::environments(Network::GnosisStaging);
+
+        assert_eq!(client.networks(), &[Network::Gnosis, Network::GnosisStaging]);
+        assert_eq!(MultiNetworkClient::environments(Network::Local).networks(), &[Network::Local]);
+    }
+
+    #[test]
+    fn test_find_first_prefers_found_value_over_errors() {
+        let not_found = |body: &str| -> eyre::Report {
+            ApiError { status: reqwest::StatusCode::NOT_FOUND, body: body.to_string() }.into()
+        };
+        let is_order_not_found =
+            |err: eyre::Report| err.downcast_ref().is_some_and(ApiError::is_order_not_found);
+        assert!(is_order_not_found(not_found(r#"{"errorType":"NotFound"}"#)));
+        assert!(!is_order_not_found(not_found("")));
+
+        let found = find_first(vec![
+            (Network::Mainnet, Err(eyre!("timeout"))),
+            (Network::MainnetStaging, Ok(Some(1))),
+        ]);
+        assert_eq!(found.unwrap(), Some((Network::MainnetStaging, 1)));
+
+        let missing = find_first::<u32>(vec![(Network::Mainnet, Ok(None))]);
+        assert_eq!(missing.unwrap(), None);
+
+        let failed = find_first::<u32>(vec![
+            (Network::Mainnet, Ok(None)),
+            (Network::MainnetStaging, Err(eyre!("timeout"))),
+        ]);
+        assert!(failed.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_merges_results() {
+        let client = MultiNetworkClient::new([Network::Mainnet, Network::Gnosis]);
+
+        let results = client
+            .fan_out(|network, _| async move {
+                match network {
+                    Network::Mainnet => Ok(vec![1, 2]),
+                    _ => Ok(vec![3]),
+                }
+            })
+            .await;
+
+        assert_eq!(
+            merge(results).unwrap(),
+            vec![(Network::Mainnet, 1), (Network::Mainnet, 2), (Network::Gnosis, 3)]
+        );
+    }
+}
@@ -0,0 +1,234 @@
+//! Client-side rate limiting and handling of `429 Too Many Requests` responses.
+
+use std::{
+    sync::{
+        Mutex, PoisonError,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use eyre::{Result, eyre};
+use reqwest::{
+    StatusCode,
+    header::{HeaderMap, RETRY_AFTER},
+};
+use reqwest_middleware::Error;
+use reqwest_retry::{
+    Retryable, RetryableStrategy, default_on_request_failure, default_on_request_success,
+};
+
+/// Times a rate-limited request is retried after waiting for `Retry-After`.
+pub const MAX_RATE_LIMITED_RETRIES: u32 = 3;
+
+/// Longest `Retry-After` honoured, longer delays are capped.
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Delay before retrying a rate-limited response without `Retry-After`,
+/// doubled on each retry.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// Requests allowed by a token bucket.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// Rate the bucket refills at, positive and finite.
+    requests_per_second: f64,
+    /// Requests that can be sent at once after idling, at least one.
+    burst: u32,
+}
+
+impl RateLimit {
+    /// Allows `requests_per_second`, which must be positive and finite.
+    pub fn per_second(requests_per_second: f64) -> Result<Self> {
+        if !(requests_per_second.is_finite() && requests_per_second > 0.0) {
+            return Err(eyre!("Rate limit must be positive, got {}", requests_per_second));
+        }
+        Ok(Self { requests_per_second, burst: 1 })
+    }
+
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+
+    pub fn requests_per_second(&self) -> f64 {
+        self.requests_per_second
+    }
+
+    pub fn burst_size(&self) -> u32 {
+        self.burst
+    }
+}
+
+/// Token bucket shared by all requests of a client.
+#[derive(Debug)]
+pub struct TokenBucket {
+    rate_limit: RateLimit,
+    state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(rate_limit: RateLimit) -> Self {
+        let state = BucketState { tokens: rate_limit.burst as f64, updated_at: Instant::now() };
+        Self { rate_limit, state: Mutex::new(state) }
+    }
+
+    /// Takes a token, returning how long to wait before sending the request.
+    /// Tokens taken ahead of time queue later callers behind earlier ones.
+    pub fn reserve(&self) -> Duration {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+        let refilled = now.duration_since(state.updated_at).as_secs_f64()
+            * self.rate_limit.requests_per_second;
+        state.tokens = (state.tokens + refilled).min(self.rate_limit.burst as f64) - 1.0;
+        state.updated_at = now;
+
+        if state.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-state.tokens / self.rate_limit.requests_per_second)
+        }
+    }
+}
+
+/// Counters of the delays spent throttling requests.
+#[derive(Debug, Default)]
+pub struct ThrottleMetrics {
+    limiter_waits: AtomicU64,
+    limiter_delay_ms: AtomicU64,
+    rate_limited_responses: AtomicU64,
+    retry_after_delay_ms: AtomicU64,
+}
+
+/// Point-in-time copy of [`ThrottleMetrics`].
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct ThrottleStats {
+    /// Requests delayed by the client-side limiter.
+    pub limiter_waits: u64,
+    pub limiter_delay: Duration,
+    /// `429` responses received.
+    pub rate_limited_responses: u64,
+    /// Time waited before retrying rate-limited requests.
+    pub retry_after_delay: Duration,
+}
+
+impl ThrottleMetrics {
+    pub(crate) fn record_limiter_wait(&self, delay: Duration) {
+        self.limiter_waits.fetch_add(1, Ordering::Relaxed);
+        self.limiter_delay_ms.fetch_add(delay.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_rate_limited(&self, delay: Duration) {
+        self.rate_limited_responses.fetch_add(1, Ordering::Relaxed);
+        self.retry_after_delay_ms.fetch_add(delay.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> ThrottleStats {
+        ThrottleStats {
+            limiter_waits: self.limiter_waits.load(Ordering::Relaxed),
+            limiter_delay: Duration::from_millis(self.limiter_delay_ms.load(Ordering::Relaxed)),
+            rate_limited_responses: self.rate_limited_responses.load(Ordering::Relaxed),
+            retry_after_delay: Duration::from_millis(
+                self.retry_after_delay_ms.load(Ordering::Relaxed),
+            ),
+        }
+    }
+}
+
+/// Parses a `Retry-After` header, given either in seconds or as an HTTP date.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc);
+    Some((date - Utc::now()).to_std().unwrap_or_default())
+}
+
+/// Delay before retrying a rate-limited request for the `attempt`-th time.
+pub(crate) fn retry_delay(headers: &HeaderMap, attempt: u32) -> Duration {
+    retry_after(headers).unwrap_or(DEFAULT_RETRY_AFTER * 2u32.pow(attempt)).min(MAX_RETRY_AFTER)
+}
+
+/// Retry strategy leaving `429` responses to the client, which honours
+/// `Retry-After` instead of backing off exponentially.
+pub(crate) struct RateLimitAwareStrategy;
+
+impl RetryableStrategy for RateLimitAwareStrategy {
+    fn handle(&self, res: &Result<reqwest::Response, Error>) -> Option<Retryable> {
+        match res {
+            Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS =>
+                Some(Retryable::Fatal),
+            Ok(response) => default_on_request_success(response),
+            Err(error) => default_on_request_failure(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_rate_limit_must_be_positive() {
+        assert!(RateLimit::per_second(0.0).is_err());
+        assert!(RateLimit::per_second(-1.0).is_err());
+        assert!(RateLimit::per_second(f64::NAN).is_err());
+        assert!(RateLimit::per_second(f64::INFINITY).is_err());
+        assert_eq!(RateLimit::per_second(2.0).unwrap().burst(0).burst_size(), 1);
+    }
+
+    #[test]
+    fn test_token_bucket_delays_after_burst() {
+        let bucket = TokenBucket::new(RateLimit::per_second(10.0).unwrap().burst(2));
+
+        assert_eq!(bucket.reserve(), Duration::ZERO);
+        assert_eq!(bucket.reserve(), Duration::ZERO);
+        let delay = bucket.reserve();
+        assert!(delay > Duration::from_millis(90) && delay <= Duration::from_millis(100));
+        assert!(bucket.reserve() > delay);
+    }
+
+    #[test]
+    fn test_retry_after_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        assert_eq!(retry_delay(&headers, 2), Duration::from_secs(4));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(7)));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"));
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("3600"));
+        assert_eq!(retry_delay(&headers, 0), MAX_RETRY_AFTER);
+    }
+
+    #[test]
+    fn test_metrics_snapshot() {
+        let metrics = ThrottleMetrics::default();
+        metrics.record_limiter_wait(Duration::from_millis(250));
+        metrics.record_rate_limited(Duration::from_secs(2));
+        metrics.record_rate_limited(Duration::from_secs(1));
+
+        assert_eq!(
+            metrics.snapshot(),
+            ThrottleStats {
+                limiter_waits: 1,
+                limiter_delay: Duration::from_millis(250),
+                rate_limited_responses: 2,
+                retry_after_delay: Duration::from_secs(3),
+            }
+        );
+    }
+}
@@ -0,0 +1,442 @@
+//! Recording of Order API interactions to fixture files, and replaying them
+//! without network access for deterministic tests.
+
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+};
+
+use async_trait::async_trait;
+use eyre::{Result, WrapErr};
+use http::Extensions;
+use reqwest::{
+    Request, Response, StatusCode,
+    header::{CONTENT_TYPE, HeaderName, RETRY_AFTER},
+};
+use reqwest_middleware::{Middleware, Next};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::debug;
+use url::Url;
+
+/// Placeholder of redacted values.
+pub const REDACTED: &str = "REDACTED";
+
+/// Query parameters redacted by default.
+const DEFAULT_REDACTED_PARAMS: &[&str] = &["api_key", "apikey", "key", "token"];
+
+/// Response headers recorded. Other headers, such as cookies, are dropped.
+const RECORDED_HEADERS: [HeaderName; 2] = [CONTENT_TYPE, RETRY_AFTER];
+
+/// Request sent to the API and the response it got.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Body>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    pub body: Body,
+}
+
+/// Body of a request or response, kept as JSON when it is valid JSON so that
+/// fixtures stay readable.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Body {
+    Json(Value),
+    Text(String),
+}
+
+impl Body {
+    fn parse(bytes: &[u8]) -> Self {
+        serde_json::from_slice(bytes)
+            .map(Body::Json)
+            .unwrap_or_else(|_| Body::Text(String::from_utf8_lossy(bytes).into_owned()))
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Body::Json(value) => value.to_string().into_bytes(),
+            Body::Text(text) => text.clone().into_bytes(),
+        }
+    }
+}
+
+/// Whether a [`Recorder`] sends requests or serves recorded responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordingMode {
+    /// Sends requests and saves the interactions to the fixture file.
+    Record,
+    /// Serves the responses of the fixture file without sending requests.
+    Replay,
+}
+
+/// No recorded interaction matches a request in replay mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingInteraction {
+    pub method: String,
+    pub url: String,
+}
+
+impl fmt::Display for MissingInteraction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "No recorded response for {} {}", self.method, self.url)
+    }
+}
+
+impl std::error::Error for MissingInteraction {}
+
+/// Middleware recording or replaying interactions, added to a client with
+/// [`super::OrderApiClient::with_middleware`].
+///
+/// Only the method, URL and body of requests are recorded, so headers such as
+/// API keys never reach the fixtures. Secrets in query parameters and JSON
+/// fields are replaced with [`REDACTED`], both when recording and when
+/// matching requests to replay, so a replaying recorder needs the redactions
+/// of the recording one.
+#[derive(Debug)]
+pub struct Recorder {
+    mode: RecordingMode,
+    path: PathBuf,
+    redacted_params: Vec<String>,
+    redacted_fields: Vec<String>,
+    ignored_fields: Vec<String>,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    interactions: Vec<Interaction>,
+    /// Whether each interaction was replayed, so that repeated requests get
+    /// their responses in the recorded order.
+    replayed: Vec<bool>,
+}
+
+impl Recorder {
+    /// Records interactions to `path`, overwriting it.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self::with_interactions(RecordingMode::Record, path.into(), Vec::new())
+    }
+
+    /// Replays the interactions recorded in `path`.
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let contents = fs::read_to_string(&path)
+            .wrap_err_with(|| format!("Failed to read fixture {}", path.display()))?;
+        let interactions = serde_json::from_str(&contents)
+            .wrap_err_with(|| format!("Failed to parse fixture {}", path.display()))?;
+        Ok(Self::with_interactions(RecordingMode::Replay, path, interactions))
+    }
+
+    fn with_interactions(
+        mode: RecordingMode,
+        path: PathBuf,
+        interactions: Vec<Interaction>,
+    ) -> Self {
+        let state = State { replayed: vec![false; interactions.len()], interactions };
+        Self {
+            mode,
+            path,
+            redacted_params: DEFAULT_REDACTED_PARAMS
+                .iter()
+                .map(|param| param.to_string())
+                .collect(),
+            redacted_fields: Vec::new(),
+            ignored_fields: Vec::new(),
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Also redacts the query parameter `name`.
+    pub fn redact_query_param(mut self, name: &str) -> Self {
+        self.redacted_params.push(name.to_string());
+        self
+    }
+
+    /// Redacts the JSON fields `name` of request and response bodies, at any
+    /// depth.
+    pub fn redact_field(mut self, name: &str) -> Self {
+        self.redacted_fields.push(name.to_string());
+        self
+    }
+
+    /// Ignores the JSON fields `name` of request bodies, at any depth, when
+    /// matching requests to replay, e.g. timestamps that change between runs.
+    pub fn ignore_field(mut self, name: &str) -> Self {
+        self.ignored_fields.push(name.to_string());
+        self
+    }
+
+    pub fn mode(&self) -> RecordingMode {
+        self.mode
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Interactions recorded so far, or loaded for replay.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner).interactions.clone()
+    }
+
+    /// Request as recorded, with its secrets redacted.
+    fn recorded_request(&self, request: &Request) -> RecordedRequest {
+        let mut url = request.url().clone();
+        self.redact_url(&mut url);
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|bytes| self.redact_body(Body::parse(bytes)));
+        RecordedRequest { method: request.method().to_string(), url: url.to_string(), body }
+    }
+
+    fn redact_url(&self, url: &mut Url) {
+        if url.query().is_none() {
+            return;
+        }
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(name, value)| {
+                let redact =
+                    self.redacted_params.iter().any(|param| name.eq_ignore_ascii_case(param));
+                (name.into_owned(), if redact { REDACTED.to_string() } else { value.into_owned() })
+            })
+            .collect();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+
+    fn redact_body(&self, body: Body) -> Body {
+        match body {
+            Body::Json(mut value) => {
+                redact_fields(&mut value, &self.redacted_fields);
+                Body::Json(value)
+            }
+            text => text,
+        }
+    }
+
+    /// Takes the first recorded response to `request` not replayed yet.
+    fn replay_response(&self, request: &RecordedRequest) -> Option<RecordedResponse> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let State { interactions, replayed } = &mut *state;
+        let index =
+            interactions.iter().zip(replayed.iter()).position(|(interaction, replayed)| {
+                !replayed && self.matches(&interaction.request, request)
+            })?;
+        replayed[index] = true;
+        Some(interactions[index].response.clone())
+    }
+
+    /// Whether `request` matches the recorded one, apart from the ignored
+    /// fields of their bodies.
+    fn matches(&self, recorded: &RecordedRequest, request: &RecordedRequest) -> bool {
+        let body = |body: &Option<Body>| match body {
+            Some(Body::Json(value)) => {
+                let mut value = value.clone();
+                redact_fields(&mut value, &self.ignored_fields);
+                Some(Body::Json(value))
+            }
+            body => body.clone(),
+        };
+        recorded.method == request.method
+            && recorded.url == request.url
+            && body(&recorded.body) == body(&request.body)
+    }
+
+    /// Appends an interaction and saves every interaction recorded so far.
+    fn save(&self, interaction: Interaction) -> Result<()> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.interactions.push(interaction);
+        state.replayed.push(false);
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .wrap_err_with(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let contents = serde_json::to_string_pretty(&state.interactions)
+            .wrap_err("Failed to serialize interactions")?;
+        fs::write(&self.path, contents + "\n")
+            .wrap_err_with(|| format!("Failed to write fixture {}", self.path.display()))
+    }
+}
+
+#[async_trait]
+impl Middleware for Recorder {
+    async fn handle(
+        &self,
+        request: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let recorded = self.recorded_request(&request);
+        match self.mode {
+            RecordingMode::Replay => {
+                debug!("Replaying {} {}", recorded.method, recorded.url);
+                let response = self.replay_response(&recorded).ok_or_else(|| {
+                    reqwest_middleware::Error::middleware(MissingInteraction {
+                        method: recorded.method,
+                        url: recorded.url,
+                    })
+                })?;
+                Ok(into_response(&response))
+            }
+            RecordingMode::Record => {
+                let response = next.run(request, extensions).await?;
+                let status = response.status().as_u16();
+                let headers = RECORDED_HEADERS
+                    .iter()
+                    .filter_map(|name| {
+                        let value = response.headers().get(name)?.to_str().ok()?;
+                        Some((name.to_string(), value.to_string()))
+                    })
+                    .collect();
+                let bytes = response.bytes().await?;
+                let response = RecordedResponse {
+                    status,
+                    headers,
+                    body: self.redact_body(Body::parse(&bytes)),
+                };
+                debug!("Recording {} {}", recorded.method, recorded.url);
+                self.save(Interaction { request: recorded, response: response.clone() }).map_err(
+                    |err| {
+                        reqwest_middleware::Error::middleware(io::Error::other(format!(
+                            "{:#}",
+                            err
+                        )))
+                    },
+                )?;
+                Ok(into_response(&response))
+            }
+        }
+    }
+}
+
+/// Replaces the values of the fields `names` of `value` with [`REDACTED`].
+fn redact_fields(value: &mut Value, names: &[String]) {
+    match value {
+        Value::Object(object) =>
+            for (name, field) in object {
+                if names.contains(name) {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact_fields(field, names);
+                }
+            },
+        Value::Array(values) => values.iter_mut().for_each(|value| redact_fields(value, names)),
+        _ => {}
+    }
+}
+
+fn into_response(recorded: &RecordedResponse) -> Response {
+    let mut response = http::Response::new(recorded.body.to_bytes());
+    *response.status_mut() = StatusCode::from_u16(recorded.status).unwrap_or_default();
+    for (name, value) in &recorded.headers {
+        if let (Ok(name), Ok(value)) = (name.parse::<HeaderName>(), value.parse()) {
+            response.headers_mut().insert(name, value);
+        }
+    }
+    Response::from(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn request(url: &str, body: Option<Value>) -> Request {
+        let mut request = Request::new(reqwest::Method::POST, url.parse().unwrap());
+        *request.body_mut() = body.map(|body| body.to_string().into());
+        request
+    }
+
+    #[test]
+    fn test_recorded_request_is_redacted() {
+        let recorder = Recorder::record("unused.json").redact_field("signature");
+
+        let recorded = recorder.recorded_request(&request(
+            "https://api.cow.fi/mainnet/api/v1/quote?apiKey=secret&limit=1",
+            Some(json!({ "order": { "signature": "0x1234", "kind": "sell" } })),
+        ));
+
+        assert_eq!(recorded.url, "https://api.cow.fi/mainnet/api/v1/quote?apiKey=REDACTED&limit=1");
+        assert_eq!(
+            recorded.body,
+            Some(Body::Json(json!({ "order": { "signature": REDACTED, "kind": "sell" } })))
+        );
+    }
+
+    #[test]
+    fn test_replay_serves_repeated_requests_in_order() {
+        let request = RecordedRequest {
+            method: "GET".to_string(),
+            url: "https://api.cow.fi/mainnet/api/v1/version".to_string(),
+            body: None,
+        };
+        let response = |body: &str| RecordedResponse {
+            status: 200,
+            headers: BTreeMap::new(),
+            body: Body::Text(body.to_string()),
+        };
+        let recorder = Recorder::with_interactions(
+            RecordingMode::Replay,
+            PathBuf::new(),
+            vec![
+                Interaction { request: request.clone(), response: response("v1") },
+                Interaction { request: request.clone(), response: response("v2") },
+            ],
+        );
+
+        assert_eq!(recorder.replay_response(&request).unwrap().body, Body::Text("v1".into()));
+        assert_eq!(recorder.replay_response(&request).unwrap().body, Body::Text("v2".into()));
+        assert_eq!(recorder.replay_response(&request), None);
+    }
+
+    #[test]
+    fn test_body_keeps_json() {
+        assert_eq!(Body::parse(br#"{"a":1}"#), Body::Json(json!({ "a": 1 })));
+        assert_eq!(Body::parse(b"v2.300.0"), Body::Text("v2.300.0".to_string()));
+        assert_eq!(Body::Json(json!("uid")).to_bytes(), br#""uid""#);
+    }
+
+    #[test]
+    fn test_replay_ignores_fields() {
+        let request = |creation_date: &str| RecordedRequest {
+            method: "POST".to_string(),
+            url: "https://api.cow.fi/mainnet/api/v1/orders".to_string(),
+            body: Some(Body::Json(json!({ "kind": "sell", "creationDate": creation_date }))),
+        };
+        let response = RecordedResponse {
+            status: 201,
+            headers: BTreeMap::new(),
+            body: Body::Json(json!("uid")),
+        };
+        let interactions = vec![Interaction { request: request("2024-01-01T00:00:00Z"), response }];
+        let strict = Recorder::with_interactions(
+            RecordingMode::Replay,
+            PathBuf::new(),
+            interactions.clone(),
+        );
+        let lenient =
+            Recorder::with_interactions(RecordingMode::Replay, PathBuf::new(), interactions)
+                .ignore_field("creationDate");
+
+        assert_eq!(strict.replay_response(&request("2025-01-01T00:00:00Z")), None);
+        assert!(lenient.replay_response(&request("2025-01-01T00:00:00Z")).is_some());
+    }
+}
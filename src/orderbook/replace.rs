@@ -0,0 +1,189 @@
+//! Replacing an open order with a new one, e.g. to update a limit price.
+
+use std::fmt;
+
+use alloy::{signers::Signer, sol_types::Eip712Domain};
+use eyre::Report;
+use tracing::{info, warn};
+
+use super::OrderApiClient;
+use crate::{
+    models::order::{OrderCancellations, OrderCreation},
+    primitives::{app_data::AppDataDocument, order_data::OrderData, order_uid::OrderUid},
+};
+
+/// How the old order is cancelled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReplaceMode {
+    /// The new order's app data names the old order in `replacedOrder`, and
+    /// the orderbook cancels it when creating the new order.
+    #[default]
+    Native,
+    /// The old order is cancelled first and the new order is posted after,
+    /// so that both orders are never open at the same time.
+    CancelThenCreate,
+}
+
+/// Step of a replacement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplaceStep {
+    /// Setting the app data of the new order.
+    PrepareOrder,
+    SignCancellation,
+    SignOrder,
+    Cancel,
+    Post,
+}
+
+/// Failed replacement, with the step that failed.
+#[derive(Debug)]
+pub struct ReplaceError {
+    pub step: ReplaceStep,
+    /// Whether the old order was cancelled before the failure, leaving the
+    /// owner without an open order.
+    pub old_order_cancelled: bool,
+    pub source: Report,
+}
+
+impl fmt::Display for ReplaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Order replacement failed at {:?}", self.step)?;
+        if self.old_order_cancelled {
+            write!(f, " after cancelling the old order")?;
+        }
+        write!(f, ": {:#}", self.source)
+    }
+}
+
+impl std::error::Error for ReplaceError {}
+
+/// Successful replacement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Replacement {
+    pub old_uid: OrderUid,
+    pub new_uid: OrderUid,
+    pub mode: ReplaceMode,
+}
+
+impl OrderApiClient {
+    /// Replaces the order `old_uid` with `new_order`, whose app data hash is
+    /// set from `app_data`.
+    ///
+    /// Everything is signed before anything is submitted, so a rejected
+    /// signature leaves the old order untouched.
+    pub async fn replace_order<S: Signer + Sync>(
+        &self,
+        old_uid: OrderUid,
+        new_order: OrderData,
+        app_data: AppDataDocument,
+        mode: ReplaceMode,
+        domain: &Eip712Domain,
+        signer: &S,
+    ) -> Result<Replacement, ReplaceError> {
+        let fail = |step, old_order_cancelled| {
+            move |source| ReplaceError { step, old_order_cancelled, source }
+        };
+
+        let (new_order, full_app_data) = prepare_replacement(old_uid, new_order, app_data, mode)
+            .map_err(fail(ReplaceStep::PrepareOrder, false))?;
+        let cancellation = match mode {
+            ReplaceMode::Native => None,
+            ReplaceMode::CancelThenCreate => Some(
+                OrderCancellations::sign(vec![old_uid], domain, signer)
+                    .await
+                    .map_err(fail(ReplaceStep::SignCancellation, false))?,
+            ),
+        };
+        let signature =
+            new_order.sign(domain, signer).await.map_err(fail(ReplaceStep::SignOrder, false))?;
+        let creation = OrderCreation::new(&new_order, &signature, signer.address())
+            .full_app_data(full_app_data);
+
+        if let Some(cancellation) = &cancellation {
+            self.cancel_order(cancellation).await.map_err(fail(ReplaceStep::Cancel, false))?;
+            info!("Cancelled order {} for replacement", old_uid);
+        }
+        let new_uid = self.post_order(&creation).await.map_err(|source| {
+            if cancellation.is_some() {
+                warn!("Cancelled order {} but failed to post its replacement", old_uid);
+            }
+            fail(ReplaceStep::Post, cancellation.is_some())(source)
+        })?;
+        info!("Replaced order {} with {}", old_uid, new_uid);
+
+        Ok(Replacement { old_uid, new_uid, mode })
+    }
+}
+
+/// Sets the app data of the new order, naming the old order for a native
+/// replacement, and returns it with the full app data document.
+fn prepare_replacement(
+    old_uid: OrderUid,
+    mut new_order: OrderData,
+    mut app_data: AppDataDocument,
+    mode: ReplaceMode,
+) -> eyre::Result<(OrderData, String)> {
+    if mode == ReplaceMode::Native {
+        app_data = app_data.replaced_order(old_uid);
+    }
+    let full_app_data = app_data.to_json()?;
+    new_order.app_data = app_data.hash()?;
+    Ok((new_order, full_app_data))
+}
+
+#[cfg(test)]
+mod tests {
+    use eyre::eyre;
+
+    use super::*;
+    use crate::{primitives::order_data::OrderKind, test_utils::order_data};
+
+    #[test]
+    fn test_native_replacement_names_old_order() {
+        let old_uid = OrderUid::new([0x11; 56].into());
+
+        let (order, full_app_data) = prepare_replacement(
+            old_uid,
+            order_data(OrderKind::Sell, 1000, 2000),
+            AppDataDocument::new("CoW Swap"),
+            ReplaceMode::Native,
+        )
+        .unwrap();
+
+        let document = AppDataDocument::from_json(&full_app_data).unwrap();
+        assert_eq!(document.metadata.replaced_order.map(|replaced| replaced.uid), Some(old_uid));
+        assert_eq!(order.app_data, document.hash().unwrap());
+    }
+
+    #[test]
+    fn test_cancel_then_create_keeps_app_data() {
+        let old_uid = OrderUid::new([0x11; 56].into());
+        let app_data = AppDataDocument::new("CoW Swap");
+
+        let (order, full_app_data) = prepare_replacement(
+            old_uid,
+            order_data(OrderKind::Sell, 1000, 2000),
+            app_data.clone(),
+            ReplaceMode::CancelThenCreate,
+        )
+        .unwrap();
+
+        assert_eq!(full_app_data, app_data.to_json().unwrap());
+        assert_eq!(order.app_data, app_data.hash().unwrap());
+    }
+
+    #[test]
+    fn test_replace_error_reports_cancelled_old_order() {
+        let error = ReplaceError {
+            step: ReplaceStep::Post,
+            old_order_cancelled: true,
+            source: eyre!("HTTP Error 400 Bad Request"),
+        };
+
+        assert_eq!(
+            error.to_string(),
+            "Order replacement failed at Post after cancelling the old order: HTTP Error 400 Bad \
+             Request"
+        );
+    }
+}
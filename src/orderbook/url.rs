@@ -130,15 +130,37 @@ impl OrderApiUrl {
         offset: Option<u32>,
         limit: Option<u32>,
     ) -> Result<String> {
-        let query = match (offset, limit) {
-            (Some(offset), Some(limit)) => format!("offset={}&limit={}", offset, limit),
-            (Some(offset), None) => format!("offset={}", offset),
-            (None, Some(limit)) => format!("limit={}", limit),
-            (None, None) => String::new(),
-        };
         let url = RequestBuilder::new()
             .path(&format!("/api/v1/account/{account}/orders"))
-            .query(&query)
+            .query(&pagination_query(offset, limit))
+            .build(&self.base_url)?;
+        Ok(url.to_string())
+    }
+
+    /// Endpoint to get orders by account, version 2
+    pub fn get_user_orders_v2(
+        &self,
+        account: &str,
+        offset: Option<u32>,
+        limit: Option<u32>,
+    ) -> Result<String> {
+        let url = RequestBuilder::new()
+            .path(&format!("/api/v2/account/{account}/orders"))
+            .query(&pagination_query(offset, limit))
+            .build(&self.base_url)?;
+        Ok(url.to_string())
+    }
+
+    /// Endpoint to look up several orders by their IDs
+    pub fn orders_lookup(&self) -> Result<String> {
+        let url = RequestBuilder::new().path("/api/v1/orders/lookup").build(&self.base_url)?;
+        Ok(url.to_string())
+    }
+
+    /// Endpoint to get diagnostics of an order
+    pub fn get_order_debug(&self, order_id: &str) -> Result<String> {
+        let url = RequestBuilder::new()
+            .path(&format!("/api/v1/orders/{}/debug", order_id))
             .build(&self.base_url)?;
         Ok(url.to_string())
     }
@@ -210,6 +232,16 @@ impl OrderApiUrl {
     }
 }
 
+/// Query string of paginated endpoints.
+fn pagination_query(offset: Option<u32>, limit: Option<u32>) -> String {
+    match (offset, limit) {
+        (Some(offset), Some(limit)) => format!("offset={}&limit={}", offset, limit),
+        (Some(offset), None) => format!("offset={}", offset),
+        (None, Some(limit)) => format!("limit={}", limit),
+        (None, None) => String::new(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use alloy::primitives::Address;
@@ -325,6 +357,27 @@ mod tests {
         assert_eq!(url.unwrap().to_string().to_lowercase(), "https://api.cow.fi/mainnet/api/v1/account/0xd8da6bf26964af9d7eed9e03e53415d37aa96045/orders?limit=10");
     }
 
+    #[test]
+    fn test_order_api_url_can_build_get_user_orders_v2() {
+        let api_url = OrderApiUrl::new(BASE_URL).unwrap();
+        let url = api_url.get_user_orders_v2(ACCOUNT, Some(10), Some(5));
+        assert_eq!(url.unwrap().to_string(), "https://api.cow.fi/mainnet/api/v2/account/0xd8da6bf26964af9d7eed9e03e53415d37aa96045/orders?offset=10&limit=5");
+    }
+
+    #[test]
+    fn test_order_api_url_can_build_orders_lookup() {
+        let api_url = OrderApiUrl::new(BASE_URL).unwrap();
+        let url = api_url.orders_lookup();
+        assert_eq!(url.unwrap().to_string(), "https://api.cow.fi/mainnet/api/v1/orders/lookup");
+    }
+
+    #[test]
+    fn test_order_api_url_can_build_get_order_debug() {
+        let api_url = OrderApiUrl::new(BASE_URL).unwrap();
+        let url = api_url.get_order_debug(ORDER_ID);
+        assert_eq!(url.unwrap().to_string(), "https://api.cow.fi/mainnet/api/v1/orders/0xeaef82ff8696bff255e130b266231acb53a8f02823ed89b33acda5fd3987a53ad8da6bf26964af9d7eed9e03e53415d37aa96045676d56da/debug");
+    }
+
     #[test]
     fn test_order_api_url_can_build_get_native_price() {
         let token_address: Address = TOKEN_ADDRESS.parse().unwrap();
@@ -1,6 +1,6 @@
 use eyre::{Report, WrapErr};
-use log::{debug, error};
 use serde::de::DeserializeOwned;
+use tracing::{debug, error};
 
 /// Parses a JSON response body into the specified type.
 pub fn parse_response_body<T: DeserializeOwned>(body: &str) -> Result<T, Report> {
@@ -0,0 +1,236 @@
+//! EIP-2612 permits allowing the vault relayer to spend tokens without a prior
+//! approval transaction, executed as a pre-hook of the order.
+
+use alloy::{
+    primitives::{Address, B256, Bytes, PrimitiveSignature, U256},
+    providers::Provider,
+    rpc::types::TransactionRequest,
+    signers::Signer,
+    sol,
+    sol_types::{Eip712Domain, SolCall, SolStruct, eip712_domain},
+};
+use eyre::{Result, WrapErr};
+use tracing::{debug, warn};
+
+use crate::{config::Network, primitives::hooks::CoWHook};
+
+/// Gas limit used for permit hooks when it cannot be estimated.
+pub const DEFAULT_PERMIT_GAS_LIMIT: u64 = 80_000;
+
+/// Version assumed for tokens that do not expose `version()`.
+const DEFAULT_PERMIT_VERSION: &str = "1";
+
+sol! {
+    struct Permit {
+        address owner;
+        address spender;
+        uint256 value;
+        uint256 nonce;
+        uint256 deadline;
+    }
+
+    #[sol(rpc)]
+    #[allow(clippy::too_many_arguments)]
+    interface IERC20Permit {
+        function name() external view returns (string);
+        function version() external view returns (string);
+        function nonces(address owner) external view returns (uint256);
+        function permit(
+            address owner,
+            address spender,
+            uint256 value,
+            uint256 deadline,
+            uint8 v,
+            bytes32 r,
+            bytes32 s
+        ) external;
+    }
+}
+
+/// An EIP-2612 permit granting the vault relayer an allowance.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Eip2612Permit {
+    pub token: Address,
+    pub owner: Address,
+    pub spender: Address,
+    pub value: U256,
+    pub nonce: U256,
+    pub deadline: U256,
+    /// EIP-712 domain of the token.
+    pub domain: Eip712Domain,
+}
+
+impl Eip2612Permit {
+    /// Builds a permit for the vault relayer of `network` from known token
+    /// details, for when no provider is available. The nonce defaults to zero
+    /// and the token version to `"1"`.
+    pub fn new(
+        network: &Network,
+        token: Address,
+        token_name: &str,
+        owner: Address,
+        value: U256,
+        deadline: U256,
+    ) -> Self {
+        let domain = eip712_domain! {
+            name: token_name.to_string(),
+            version: DEFAULT_PERMIT_VERSION,
+            chain_id: network.chain_id(),
+            verifying_contract: token,
+        };
+        Self {
+            token,
+            owner,
+            spender: network.vault_relayer(),
+            value,
+            nonce: U256::ZERO,
+            deadline,
+            domain,
+        }
+    }
+
+    /// Sets the owner's current permit nonce.
+    pub fn nonce(mut self, nonce: U256) -> Self {
+        self.nonce = nonce;
+        self
+    }
+
+    /// Sets the version of the token's EIP-712 domain.
+    pub fn version(mut self, version: &str) -> Self {
+        self.domain.version = Some(version.to_string().into());
+        self
+    }
+
+    /// Builds a permit for the vault relayer of `network`, reading the token
+    /// name, version and the owner's nonce from the chain.
+    pub async fn fetch<P: Provider>(
+        provider: &P,
+        network: &Network,
+        token: Address,
+        owner: Address,
+        value: U256,
+        deadline: U256,
+    ) -> Result<Self> {
+        let contract = IERC20Permit::new(token, provider);
+        let name = contract.name().call().await.wrap_err("Failed to read token name")?._0;
+        let nonce = contract.nonces(owner).call().await.wrap_err("Failed to read permit nonce")?._0;
+        let permit = Self::new(network, token, &name, owner, value, deadline).nonce(nonce);
+        match contract.version().call().await {
+            Ok(version) => Ok(permit.version(&version._0)),
+            Err(err) => {
+                debug!("Token {} has no version(), using default: {}", token, err);
+                Ok(permit)
+            }
+        }
+    }
+
+    /// EIP-712 digest signed by the owner.
+    pub fn digest(&self) -> B256 {
+        Permit {
+            owner: self.owner,
+            spender: self.spender,
+            value: self.value,
+            nonce: self.nonce,
+            deadline: self.deadline,
+        }
+        .eip712_signing_hash(&self.domain)
+    }
+
+    /// Signs the permit with the owner's signer.
+    pub async fn sign<S: Signer + Sync>(&self, signer: &S) -> Result<PrimitiveSignature> {
+        signer.sign_hash(&self.digest()).await.wrap_err("Failed to sign permit")
+    }
+
+    /// Calldata for `permit` on the token.
+    pub fn calldata(&self, signature: &PrimitiveSignature) -> Bytes {
+        IERC20Permit::permitCall {
+            owner: self.owner,
+            spender: self.spender,
+            value: self.value,
+            deadline: self.deadline,
+            v: 27 + u8::from(signature.v()),
+            r: signature.r().into(),
+            s: signature.s().into(),
+        }
+        .abi_encode()
+        .into()
+    }
+
+    /// Signs the permit and wraps it into a pre-hook. The gas limit is
+    /// estimated with `provider` when given, falling back to
+    /// [`DEFAULT_PERMIT_GAS_LIMIT`].
+    pub async fn to_hook<S: Signer + Sync, P: Provider>(
+        &self,
+        signer: &S,
+        provider: Option<&P>,
+    ) -> Result<CoWHook> {
+        let signature = self.sign(signer).await?;
+        let call_data = self.calldata(&signature);
+
+        let gas_limit = match provider {
+            Some(provider) => {
+                let tx =
+                    TransactionRequest::default().to(self.token).input(call_data.clone().into());
+                provider.estimate_gas(tx).await.unwrap_or_else(|err| {
+                    warn!("Failed to estimate permit gas, using default: {}", err);
+                    DEFAULT_PERMIT_GAS_LIMIT
+                })
+            }
+            None => DEFAULT_PERMIT_GAS_LIMIT,
+        };
+
+        Ok(CoWHook::new(self.token, call_data, gas_limit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::address,
+        providers::{ProviderBuilder, RootProvider},
+        signers::local::PrivateKeySigner,
+    };
+
+    use super::*;
+
+    /// Permit of mainnet USDC, whose EIP-712 domain is version 2.
+    fn permit(owner: Address) -> Eip2612Permit {
+        Eip2612Permit::new(
+            &Network::Mainnet,
+            address!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+            "USD Coin",
+            owner,
+            U256::MAX,
+            U256::from(1_700_000_000),
+        )
+        .version("2")
+    }
+
+    #[tokio::test]
+    async fn test_permit_hook_recovers_owner() {
+        let signer = PrivateKeySigner::random();
+        let permit = permit(signer.address());
+
+        let hook = permit.to_hook(&signer, None::<&RootProvider>).await.unwrap();
+        let call = IERC20Permit::permitCall::abi_decode(&hook.call_data, true).unwrap();
+        let signature = PrimitiveSignature::from_scalars_and_parity(call.r, call.s, call.v == 28);
+
+        assert_eq!(hook.target, permit.token);
+        assert_eq!(hook.gas_limit, DEFAULT_PERMIT_GAS_LIMIT);
+        assert_eq!(call.spender, Network::Mainnet.vault_relayer());
+        assert_eq!(
+            signature.recover_address_from_prehash(&permit.digest()).unwrap(),
+            signer.address()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_permit_hook_falls_back_when_estimation_fails() {
+        let signer = PrivateKeySigner::random();
+        let provider = ProviderBuilder::new().on_http("http://127.0.0.1:1".parse().unwrap());
+
+        let hook = permit(signer.address()).to_hook(&signer, Some(&provider)).await.unwrap();
+
+        assert_eq!(hook.gas_limit, DEFAULT_PERMIT_GAS_LIMIT);
+    }
+}
@@ -5,7 +5,19 @@ use std::{
     str::FromStr,
 };
 
+use alloy::primitives::keccak256;
+use eyre::{Result, WrapErr};
 use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+use serde_json::{Map, Value};
+
+use crate::primitives::{
+    hooks::{CoWHook, OrderInteractionHooks},
+    order_uid::OrderUid,
+    partner_fee::{PartnerFee, PartnerFees},
+};
+
+/// Latest version of the app data schema.
+pub const LATEST_APP_DATA_VERSION: &str = "1.3.0";
 
 #[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
 pub struct AppDataHash(pub [u8; 32]);
@@ -84,3 +96,164 @@ pub struct FullAppData {
     pub version: String,
     pub metadata: String,
 }
+
+/// Full app data document, whose keccak256 hash is signed as the order's
+/// `appData`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppDataDocument {
+    pub version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+    #[serde(default)]
+    pub metadata: AppDataMetadata,
+}
+
+/// Metadata of an app data document.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppDataMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<OrderInteractionHooks>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partner_fee: Option<PartnerFees>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaced_order: Option<ReplacedOrder>,
+    /// Metadata not modelled by this crate, preserved as is.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// Order that the orderbook cancels when the order carrying this metadata is
+/// created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ReplacedOrder {
+    pub uid: OrderUid,
+}
+
+impl AppDataDocument {
+    pub fn new(app_code: &str) -> Self {
+        Self {
+            version: LATEST_APP_DATA_VERSION.to_string(),
+            app_code: Some(app_code.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Parses a full app data document.
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).wrap_err("Failed to parse app data document")
+    }
+
+    /// Adds a hook executed before the order is settled.
+    pub fn pre_hook(mut self, hook: CoWHook) -> Self {
+        self.metadata.hooks.get_or_insert_with(Default::default).pre.push(hook);
+        self
+    }
+
+    /// Adds a hook executed after the order is settled.
+    pub fn post_hook(mut self, hook: CoWHook) -> Self {
+        self.metadata.hooks.get_or_insert_with(Default::default).post.push(hook);
+        self
+    }
+
+    /// Adds a partner fee. Adding more than one fee stores them as a list.
+    pub fn partner_fee(mut self, fee: PartnerFee) -> Self {
+        match &mut self.metadata.partner_fee {
+            Some(fees) => fees.push(fee),
+            None => self.metadata.partner_fee = Some(PartnerFees::Single(fee)),
+        }
+        self
+    }
+
+    /// Replaces an order of the same owner, which the orderbook cancels when
+    /// this order is created.
+    pub fn replaced_order(mut self, uid: OrderUid) -> Self {
+        self.metadata.replaced_order = Some(ReplacedOrder { uid });
+        self
+    }
+
+    /// Total partner fee charged on the volume, in basis points.
+    pub fn partner_fee_volume_bps(&self) -> u32 {
+        self.metadata.partner_fee.as_ref().map(PartnerFees::volume_bps).unwrap_or_default()
+    }
+
+    /// Serializes the document with sorted keys, so that the same document
+    /// always has the same hash.
+    pub fn to_json(&self) -> Result<String> {
+        let value = serde_json::to_value(self).wrap_err("Failed to serialize app data document")?;
+        Ok(value.to_string())
+    }
+
+    /// Hash of the serialized document, to be used as the order's `appData`.
+    pub fn hash(&self) -> Result<AppDataHash> {
+        Ok(AppDataHash(keccak256(self.to_json()?).0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{address, bytes};
+
+    use super::*;
+
+    #[test]
+    fn test_document_with_hooks_serializes_sorted() {
+        let hook = CoWHook::new(
+            address!("0x0000000000000000000000000000000000000001"),
+            bytes!("0x1234"),
+            50_000,
+        )
+        .dapp_id("permit");
+
+        let document = AppDataDocument::new("CoW Swap").pre_hook(hook);
+
+        assert_eq!(
+            document.to_json().unwrap(),
+            r#"{"appCode":"CoW Swap","metadata":{"hooks":{"pre":[{"callData":"0x1234","dappId":"permit","gasLimit":"50000","target":"0x0000000000000000000000000000000000000001"}]}},"version":"1.3.0"}"#
+        );
+    }
+
+    #[test]
+    fn test_document_with_multiple_partner_fees() {
+        let recipient = address!("0x0000000000000000000000000000000000000001");
+
+        let document = AppDataDocument::new("CoW Swap")
+            .partner_fee(PartnerFee::volume(50, recipient))
+            .partner_fee(PartnerFee::surplus(10, 100, recipient));
+
+        assert!(
+            matches!(document.metadata.partner_fee, Some(PartnerFees::Multiple(ref fees)) if fees.len() == 2)
+        );
+        assert_eq!(document.partner_fee_volume_bps(), 50);
+    }
+
+    #[test]
+    fn test_document_round_trip_preserves_unknown_metadata() {
+        let json =
+            r#"{"appCode":"CoW Swap","metadata":{"quote":{"slippageBips":50}},"version":"1.1.0"}"#;
+
+        let document = AppDataDocument::from_json(json).unwrap();
+
+        assert!(document.metadata.hooks.is_none());
+        assert_eq!(document.to_json().unwrap(), json);
+        assert_eq!(document.hash().unwrap(), AppDataHash(keccak256(json).0));
+    }
+
+    #[test]
+    fn test_document_with_replaced_order() {
+        let uid = OrderUid::new([0x11; 56].into());
+
+        let document = AppDataDocument::new("CoW Swap").replaced_order(uid);
+
+        assert_eq!(
+            document.to_json().unwrap(),
+            format!(
+                r#"{{"appCode":"CoW Swap","metadata":{{"replacedOrder":{{"uid":"0x{}"}}}},"version":"1.3.0"}}"#,
+                "11".repeat(56)
+            )
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use alloy::primitives::{Address, Bytes};
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+
+/// A call executed by the settlement contract before or after the order is
+/// settled, e.g. a permit or a bridging transfer.
+#[serde_as]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoWHook {
+    pub target: Address,
+    pub call_data: Bytes,
+    /// Gas the solver must provide for the call.
+    #[serde_as(as = "DisplayFromStr")]
+    pub gas_limit: u64,
+    /// Identifier of the dapp that created the hook.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dapp_id: Option<String>,
+}
+
+impl CoWHook {
+    pub fn new(target: Address, call_data: Bytes, gas_limit: u64) -> Self {
+        Self { target, call_data, gas_limit, dapp_id: None }
+    }
+
+    /// Sets the dapp identifier of the hook.
+    pub fn dapp_id(mut self, dapp_id: &str) -> Self {
+        self.dapp_id = Some(dapp_id.to_string());
+        self
+    }
+}
+
+/// Hooks of an order, stored in the `hooks` field of the app data metadata.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderInteractionHooks {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre: Vec<CoWHook>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post: Vec<CoWHook>,
+}
+
+impl OrderInteractionHooks {
+    pub fn is_empty(&self) -> bool {
+        self.pre.is_empty() && self.post.is_empty()
+    }
+}
@@ -1,2 +1,5 @@
 pub mod app_data;
+pub mod hooks;
+pub mod order_data;
 pub mod order_uid;
+pub mod partner_fee;
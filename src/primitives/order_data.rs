@@ -0,0 +1,421 @@
+use std::{fmt, str};
+
+use alloy::{
+    primitives::{Address, B256, PrimitiveSignature, U256, keccak256},
+    signers::Signer,
+    sol,
+    sol_types::{Eip712Domain, SolStruct},
+};
+use eyre::{Error, WrapErr, eyre};
+use serde::{Deserialize, Serialize};
+
+use crate::primitives::{app_data::AppDataHash, order_uid::OrderUid};
+
+sol! {
+    /// EIP-712 struct signed by order owners.
+    #[derive(Debug)]
+    struct Order {
+        address sellToken;
+        address buyToken;
+        address receiver;
+        uint256 sellAmount;
+        uint256 buyAmount;
+        uint32 validTo;
+        bytes32 appData;
+        uint256 feeAmount;
+        string kind;
+        bool partiallyFillable;
+        string sellTokenBalance;
+        string buyTokenBalance;
+    }
+
+    /// On-chain representation of an order, as used by the settlement and
+    /// ComposableCoW contracts.
+    library GPv2Order {
+        #[derive(Debug, PartialEq, Eq)]
+        struct Data {
+            address sellToken;
+            address buyToken;
+            address receiver;
+            uint256 sellAmount;
+            uint256 buyAmount;
+            uint32 validTo;
+            bytes32 appData;
+            uint256 feeAmount;
+            bytes32 kind;
+            bool partiallyFillable;
+            bytes32 sellTokenBalance;
+            bytes32 buyTokenBalance;
+        }
+    }
+
+    /// EIP-712 struct signed by owners to cancel orders off-chain.
+    #[derive(Debug)]
+    struct OrderCancellations {
+        bytes[] orderUids;
+    }
+}
+
+/// Whether an order sells an exact amount or buys an exact amount.
+#[derive(Debug, Default, Clone, Copy, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderKind {
+    #[default]
+    Sell,
+    Buy,
+}
+
+impl OrderKind {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            OrderKind::Sell => "sell",
+            OrderKind::Buy => "buy",
+        }
+    }
+
+    /// Marker used for the kind in the on-chain order representation.
+    pub fn marker(&self) -> B256 {
+        keccak256(self.as_str())
+    }
+
+    fn from_marker(marker: B256) -> Result<Self, Error> {
+        [OrderKind::Sell, OrderKind::Buy]
+            .into_iter()
+            .find(|kind| kind.marker() == marker)
+            .ok_or_else(|| eyre!("Unknown order kind marker: {}", marker))
+    }
+}
+
+/// Where the sell token is taken from when the order is settled.
+#[derive(Debug, Default, Clone, Copy, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SellTokenSource {
+    #[default]
+    Erc20,
+    External,
+    Internal,
+}
+
+impl SellTokenSource {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            SellTokenSource::Erc20 => "erc20",
+            SellTokenSource::External => "external",
+            SellTokenSource::Internal => "internal",
+        }
+    }
+
+    /// Marker used for the balance in the on-chain order representation.
+    pub fn marker(&self) -> B256 {
+        keccak256(self.as_str())
+    }
+
+    fn from_marker(marker: B256) -> Result<Self, Error> {
+        [SellTokenSource::Erc20, SellTokenSource::External, SellTokenSource::Internal]
+            .into_iter()
+            .find(|source| source.marker() == marker)
+            .ok_or_else(|| eyre!("Unknown sell token balance marker: {}", marker))
+    }
+}
+
+/// Where the buy token is sent to when the order is settled.
+#[derive(Debug, Default, Clone, Copy, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BuyTokenDestination {
+    #[default]
+    Erc20,
+    Internal,
+}
+
+impl BuyTokenDestination {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            BuyTokenDestination::Erc20 => "erc20",
+            BuyTokenDestination::Internal => "internal",
+        }
+    }
+
+    /// Marker used for the balance in the on-chain order representation.
+    pub fn marker(&self) -> B256 {
+        keccak256(self.as_str())
+    }
+
+    fn from_marker(marker: B256) -> Result<Self, Error> {
+        [BuyTokenDestination::Erc20, BuyTokenDestination::Internal]
+            .into_iter()
+            .find(|destination| destination.marker() == marker)
+            .ok_or_else(|| eyre!("Unknown buy token balance marker: {}", marker))
+    }
+}
+
+macro_rules! impl_str_conversions {
+    ($ty:ident, [$($variant:ident),+]) => {
+        impl fmt::Display for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl str::FromStr for $ty {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                [$($ty::$variant),+]
+                    .into_iter()
+                    .find(|value| value.as_str() == s)
+                    .ok_or_else(|| eyre!("Invalid {}: {}", stringify!($ty), s))
+            }
+        }
+    };
+}
+
+impl_str_conversions!(OrderKind, [Sell, Buy]);
+impl_str_conversions!(SellTokenSource, [Erc20, External, Internal]);
+impl_str_conversions!(BuyTokenDestination, [Erc20, Internal]);
+
+/// The signed part of an order, as hashed and verified by the settlement
+/// contract.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderData {
+    pub sell_token: Address,
+    pub buy_token: Address,
+    /// Receiver of the buy tokens, the zero address means the owner.
+    #[serde(default)]
+    pub receiver: Address,
+    pub sell_amount: U256,
+    pub buy_amount: U256,
+    pub valid_to: u32,
+    pub app_data: AppDataHash,
+    #[serde(default)]
+    pub fee_amount: U256,
+    pub kind: OrderKind,
+    pub partially_fillable: bool,
+    #[serde(default)]
+    pub sell_token_balance: SellTokenSource,
+    #[serde(default)]
+    pub buy_token_balance: BuyTokenDestination,
+}
+
+impl OrderData {
+    fn to_eip712(&self) -> Order {
+        Order {
+            sellToken: self.sell_token,
+            buyToken: self.buy_token,
+            receiver: self.receiver,
+            sellAmount: self.sell_amount,
+            buyAmount: self.buy_amount,
+            validTo: self.valid_to,
+            appData: self.app_data.0.into(),
+            feeAmount: self.fee_amount,
+            kind: self.kind.as_str().to_string(),
+            partiallyFillable: self.partially_fillable,
+            sellTokenBalance: self.sell_token_balance.as_str().to_string(),
+            buyTokenBalance: self.buy_token_balance.as_str().to_string(),
+        }
+    }
+
+    /// EIP-712 struct hash of the order.
+    pub fn hash_struct(&self) -> B256 {
+        self.to_eip712().eip712_hash_struct()
+    }
+
+    /// EIP-712 digest that the owner signs for the given domain.
+    pub fn digest(&self, domain: &Eip712Domain) -> B256 {
+        self.to_eip712().eip712_signing_hash(domain)
+    }
+
+    /// Computes the UID the orderbook and settlement contract assign to this
+    /// order when placed by `owner`.
+    pub fn uid(&self, domain: &Eip712Domain, owner: Address) -> OrderUid {
+        OrderUid::from_parts(self.digest(domain), owner, self.valid_to)
+    }
+
+    /// Signs the order with the EIP-712 scheme.
+    pub async fn sign<S: Signer + Sync>(
+        &self,
+        domain: &Eip712Domain,
+        signer: &S,
+    ) -> Result<PrimitiveSignature, Error> {
+        signer.sign_hash(&self.digest(domain)).await.wrap_err("Failed to sign order")
+    }
+}
+
+/// EIP-712 digest that an owner signs to cancel orders off-chain.
+pub fn cancellation_digest(order_uids: &[OrderUid], domain: &Eip712Domain) -> B256 {
+    OrderCancellations { orderUids: order_uids.iter().map(|uid| uid.0.to_vec().into()).collect() }
+        .eip712_signing_hash(domain)
+}
+
+impl From<&OrderData> for GPv2Order::Data {
+    fn from(order: &OrderData) -> Self {
+        GPv2Order::Data {
+            sellToken: order.sell_token,
+            buyToken: order.buy_token,
+            receiver: order.receiver,
+            sellAmount: order.sell_amount,
+            buyAmount: order.buy_amount,
+            validTo: order.valid_to,
+            appData: order.app_data.0.into(),
+            feeAmount: order.fee_amount,
+            kind: order.kind.marker(),
+            partiallyFillable: order.partially_fillable,
+            sellTokenBalance: order.sell_token_balance.marker(),
+            buyTokenBalance: order.buy_token_balance.marker(),
+        }
+    }
+}
+
+impl TryFrom<&GPv2Order::Data> for OrderData {
+    type Error = Error;
+
+    fn try_from(order: &GPv2Order::Data) -> Result<Self, Self::Error> {
+        Ok(OrderData {
+            sell_token: order.sellToken,
+            buy_token: order.buyToken,
+            receiver: order.receiver,
+            sell_amount: order.sellAmount,
+            buy_amount: order.buyAmount,
+            valid_to: order.validTo,
+            app_data: AppDataHash(order.appData.0),
+            fee_amount: order.feeAmount,
+            kind: OrderKind::from_marker(order.kind)?,
+            partially_fillable: order.partiallyFillable,
+            sell_token_balance: SellTokenSource::from_marker(order.sellTokenBalance)?,
+            buy_token_balance: BuyTokenDestination::from_marker(order.buyTokenBalance)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{address, b256};
+
+    use super::*;
+    use crate::config::Network;
+
+    fn order() -> OrderData {
+        OrderData {
+            sell_token: address!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+            buy_token: address!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+            receiver: Address::ZERO,
+            sell_amount: U256::from(1_000_000_000u64),
+            buy_amount: U256::from(500_000_000_000_000_000u64),
+            valid_to: 1_700_000_000,
+            app_data: AppDataHash::default(),
+            fee_amount: U256::ZERO,
+            kind: OrderKind::Sell,
+            partially_fillable: false,
+            sell_token_balance: SellTokenSource::Erc20,
+            buy_token_balance: BuyTokenDestination::Erc20,
+        }
+    }
+
+    #[test]
+    fn test_order_type_hash_matches_settlement_contract() {
+        assert_eq!(
+            order().to_eip712().eip712_type_hash(),
+            b256!("0xd5a25ba2e97094ad7d83dc28a6572da797d6b3e7fc6663bd93efb789fc17e489")
+        );
+    }
+
+    #[test]
+    fn test_markers_match_settlement_contract() {
+        assert_eq!(
+            OrderKind::Sell.marker(),
+            b256!("0xf3b277728b3fee749481eb3e0b3b48980dbbab78658fc419025cb16eee346775")
+        );
+        assert_eq!(
+            OrderKind::Buy.marker(),
+            b256!("0x6ed88e868af0a1983e3886d5f3e95a2fafbd6c3450bc229e27342283dc429ccc")
+        );
+        assert_eq!(
+            SellTokenSource::Erc20.marker(),
+            b256!("0x5a28e9363bb942b639270062aa6bb295f434bcdfc42c97267bf003f272060dc9")
+        );
+    }
+
+    #[test]
+    fn test_uid_contains_digest_owner_and_valid_to() {
+        let owner = address!("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045");
+        let domain = Network::Mainnet.settlement_domain();
+        let order = order();
+
+        let uid = order.uid(&domain, owner);
+
+        assert_eq!(uid.digest(), order.digest(&domain));
+        assert_eq!(uid.owner(), owner);
+        assert_eq!(uid.valid_to(), order.valid_to);
+    }
+
+    #[tokio::test]
+    async fn test_signature_recovers_owner() {
+        let signer = alloy::signers::local::PrivateKeySigner::random();
+        let domain = Network::Mainnet.settlement_domain();
+        let order = order();
+
+        let signature = order.sign(&domain, &signer).await.unwrap();
+
+        assert_eq!(
+            signature.recover_address_from_prehash(&order.digest(&domain)).unwrap(),
+            signer.address()
+        );
+    }
+
+    #[test]
+    fn test_cancellation_type() {
+        assert_eq!(
+            OrderCancellations::eip712_encode_type(),
+            "OrderCancellations(bytes[] orderUids)"
+        );
+        assert_ne!(
+            cancellation_digest(
+                &[OrderUid::new(alloy::primitives::FixedBytes::repeat_byte(1))],
+                &Network::Mainnet.settlement_domain()
+            ),
+            cancellation_digest(&[], &Network::Mainnet.settlement_domain())
+        );
+    }
+
+    #[test]
+    fn test_digest_depends_on_chain() {
+        let order = order();
+
+        assert_ne!(
+            order.digest(&Network::Mainnet.settlement_domain()),
+            order.digest(&Network::Gnosis.settlement_domain())
+        );
+    }
+
+    #[test]
+    fn test_gpv2_order_round_trip() {
+        let order = OrderData {
+            kind: OrderKind::Buy,
+            sell_token_balance: SellTokenSource::External,
+            buy_token_balance: BuyTokenDestination::Internal,
+            ..order()
+        };
+
+        let data = GPv2Order::Data::from(&order);
+
+        assert_eq!(OrderData::try_from(&data).unwrap(), order);
+    }
+
+    #[test]
+    fn test_order_data_deserializes_with_defaults() {
+        let json = r#"{
+            "sellToken": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+            "buyToken": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+            "sellAmount": "1000000000",
+            "buyAmount": "500000000000000000",
+            "validTo": 1700000000,
+            "appData": "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "kind": "sell",
+            "partiallyFillable": false
+        }"#;
+
+        let parsed: OrderData = serde_json::from_str(json).unwrap();
+
+        assert_eq!(parsed, order());
+    }
+}
@@ -1,6 +1,6 @@
 use std::{fmt, str};
 
-use alloy::primitives::FixedBytes;
+use alloy::primitives::{Address, B256, FixedBytes};
 use eyre::Error;
 use serde::{Deserialize, Serialize};
 
@@ -11,6 +11,30 @@ impl OrderUid {
     pub fn new(uid: FixedBytes<56>) -> Self {
         Self(uid)
     }
+
+    /// Builds a UID from the order digest, the owner and the order expiry.
+    pub fn from_parts(digest: B256, owner: Address, valid_to: u32) -> Self {
+        let mut uid = [0u8; 56];
+        uid[..32].copy_from_slice(digest.as_slice());
+        uid[32..52].copy_from_slice(owner.as_slice());
+        uid[52..].copy_from_slice(&valid_to.to_be_bytes());
+        Self(FixedBytes(uid))
+    }
+
+    /// EIP-712 digest of the order.
+    pub fn digest(&self) -> B256 {
+        B256::from_slice(&self.0[..32])
+    }
+
+    /// Owner of the order.
+    pub fn owner(&self) -> Address {
+        Address::from_slice(&self.0[32..52])
+    }
+
+    /// Timestamp until which the order is valid.
+    pub fn valid_to(&self) -> u32 {
+        u32::from_be_bytes(self.0[52..].try_into().expect("slice is 4 bytes"))
+    }
 }
 
 impl fmt::Display for OrderUid {
@@ -0,0 +1,116 @@
+use alloy::primitives::Address;
+use serde::{Deserialize, Serialize};
+
+/// Fee charged by an integrating partner, stored in the `partnerFee` field of
+/// the app data metadata.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(untagged, rename_all_fields = "camelCase")]
+pub enum PartnerFee {
+    /// Fee on the traded volume. Also parses the legacy `bps` field.
+    Volume {
+        #[serde(alias = "bps")]
+        volume_bps: u32,
+        recipient: Address,
+    },
+    /// Fee on the surplus, capped to a share of the volume.
+    Surplus { surplus_bps: u32, max_volume_bps: u32, recipient: Address },
+    /// Fee on the improvement over the quote, capped to a share of the volume.
+    PriceImprovement { price_improvement_bps: u32, max_volume_bps: u32, recipient: Address },
+}
+
+impl PartnerFee {
+    pub fn volume(volume_bps: u32, recipient: Address) -> Self {
+        PartnerFee::Volume { volume_bps, recipient }
+    }
+
+    pub fn surplus(surplus_bps: u32, max_volume_bps: u32, recipient: Address) -> Self {
+        PartnerFee::Surplus { surplus_bps, max_volume_bps, recipient }
+    }
+
+    pub fn price_improvement(
+        price_improvement_bps: u32,
+        max_volume_bps: u32,
+        recipient: Address,
+    ) -> Self {
+        PartnerFee::PriceImprovement { price_improvement_bps, max_volume_bps, recipient }
+    }
+
+    pub fn recipient(&self) -> Address {
+        match self {
+            PartnerFee::Volume { recipient, .. }
+            | PartnerFee::Surplus { recipient, .. }
+            | PartnerFee::PriceImprovement { recipient, .. } => *recipient,
+        }
+    }
+
+    /// Fee known upfront from the traded volume. Surplus and price
+    /// improvement fees depend on the execution and are not known in advance.
+    pub fn volume_bps(&self) -> u32 {
+        match self {
+            PartnerFee::Volume { volume_bps, .. } => *volume_bps,
+            PartnerFee::Surplus { .. } | PartnerFee::PriceImprovement { .. } => 0,
+        }
+    }
+}
+
+/// One or more partner fees.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PartnerFees {
+    Single(PartnerFee),
+    Multiple(Vec<PartnerFee>),
+}
+
+impl PartnerFees {
+    pub fn fees(&self) -> &[PartnerFee] {
+        match self {
+            PartnerFees::Single(fee) => std::slice::from_ref(fee),
+            PartnerFees::Multiple(fees) => fees,
+        }
+    }
+
+    /// Adds a fee, turning a single fee into multiple fees.
+    pub fn push(&mut self, fee: PartnerFee) {
+        match self {
+            PartnerFees::Single(existing) =>
+                *self = PartnerFees::Multiple(vec![existing.clone(), fee]),
+            PartnerFees::Multiple(fees) => fees.push(fee),
+        }
+    }
+
+    /// Total volume fee in basis points.
+    pub fn volume_bps(&self) -> u32 {
+        self.fees().iter().map(PartnerFee::volume_bps).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partner_fee_variants_deserialize() {
+        let json = r#"[
+            {"volumeBps": 50, "recipient": "0x0000000000000000000000000000000000000001"},
+            {"surplusBps": 20, "maxVolumeBps": 100, "recipient": "0x0000000000000000000000000000000000000002"},
+            {"priceImprovementBps": 30, "maxVolumeBps": 100, "recipient": "0x0000000000000000000000000000000000000003"},
+            {"bps": 10, "recipient": "0x0000000000000000000000000000000000000004"}
+        ]"#;
+
+        let fees: PartnerFees = serde_json::from_str(json).unwrap();
+
+        assert!(matches!(fees.fees()[1], PartnerFee::Surplus { surplus_bps: 20, .. }));
+        assert!(matches!(fees.fees()[2], PartnerFee::PriceImprovement { .. }));
+        assert_eq!(fees.volume_bps(), 60);
+    }
+
+    #[test]
+    fn test_volume_fee_serializes_camel_case() {
+        let fee = PartnerFee::volume(50, Address::ZERO);
+
+        assert_eq!(
+            serde_json::to_string(&fee).unwrap(),
+            r#"{"volumeBps":50,"recipient":"0x0000000000000000000000000000000000000000"}"#
+        );
+    }
+}
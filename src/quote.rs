@@ -0,0 +1,234 @@
+//! Turning quotes into the amounts displayed to users and signed in orders,
+//! accounting for network costs, partner fees and slippage.
+
+use alloy::primitives::U256;
+use eyre::{Result, eyre};
+
+pub use crate::math::Amounts;
+use crate::{
+    math::{MAX_BPS, Rounding, apply_slippage, mul_div},
+    models::response::OrderQuote,
+    primitives::{
+        app_data::AppDataDocument,
+        order_data::{OrderData, OrderKind},
+    },
+};
+
+/// Breakdown of a quote into the amounts before and after each cost.
+///
+/// Fees are charged in the surplus token: the buy token for sell orders and
+/// the sell token for buy orders.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct QuoteAmountsAndCosts {
+    pub kind: OrderKind,
+    /// Network costs, in sell token.
+    pub network_fee_amount: U256,
+    /// Partner fee, in surplus token.
+    pub partner_fee_amount: U256,
+    pub partner_fee_bps: u32,
+    pub before_network_costs: Amounts,
+    pub after_network_costs: Amounts,
+    pub after_partner_fees: Amounts,
+    /// Amounts to sign, the limit the order can be executed at.
+    pub after_slippage: Amounts,
+}
+
+impl QuoteAmountsAndCosts {
+    pub fn new(quote: &OrderQuote, partner_fee_bps: u32, slippage_bps: u32) -> Result<Self> {
+        if partner_fee_bps > MAX_BPS || slippage_bps > MAX_BPS {
+            return Err(eyre!("Partner fee and slippage must not exceed {} bps", MAX_BPS));
+        }
+
+        let overflow = || eyre!("Quote amounts overflow");
+        let network_fee_amount = quote.fee_amount;
+        let bps = |amount: U256, bps: u32| {
+            mul_div(amount, U256::from(bps), U256::from(MAX_BPS), Rounding::Down)
+                .ok_or_else(overflow)
+        };
+
+        let (before_network_costs, after_network_costs, partner_fee_amount, after_partner_fees) =
+            match quote.kind {
+                OrderKind::Sell => {
+                    // The quoted sell amount excludes network costs, the buy
+                    // amount before costs is extrapolated at the quoted price
+                    let network_fee_in_buy_token = if quote.sell_amount.is_zero() {
+                        U256::ZERO
+                    } else {
+                        mul_div(
+                            quote.buy_amount,
+                            network_fee_amount,
+                            quote.sell_amount,
+                            Rounding::Down,
+                        )
+                        .ok_or_else(overflow)?
+                    };
+                    let sell_amount =
+                        quote.sell_amount.checked_add(network_fee_amount).ok_or_else(overflow)?;
+                    let before_network_costs = Amounts {
+                        sell_amount,
+                        buy_amount: quote
+                            .buy_amount
+                            .checked_add(network_fee_in_buy_token)
+                            .ok_or_else(overflow)?,
+                    };
+                    let after_network_costs = Amounts { sell_amount, buy_amount: quote.buy_amount };
+                    let partner_fee_amount = bps(before_network_costs.buy_amount, partner_fee_bps)?;
+                    let after_partner_fees = Amounts {
+                        sell_amount,
+                        buy_amount: after_network_costs
+                            .buy_amount
+                            .saturating_sub(partner_fee_amount),
+                    };
+                    (
+                        before_network_costs,
+                        after_network_costs,
+                        partner_fee_amount,
+                        after_partner_fees,
+                    )
+                }
+                OrderKind::Buy => {
+                    let buy_amount = quote.buy_amount;
+                    let before_network_costs =
+                        Amounts { sell_amount: quote.sell_amount, buy_amount };
+                    let after_network_costs = Amounts {
+                        sell_amount: quote
+                            .sell_amount
+                            .checked_add(network_fee_amount)
+                            .ok_or_else(overflow)?,
+                        buy_amount,
+                    };
+                    let partner_fee_amount =
+                        bps(before_network_costs.sell_amount, partner_fee_bps)?;
+                    let after_partner_fees = Amounts {
+                        sell_amount: after_network_costs
+                            .sell_amount
+                            .checked_add(partner_fee_amount)
+                            .ok_or_else(overflow)?,
+                        buy_amount,
+                    };
+                    (
+                        before_network_costs,
+                        after_network_costs,
+                        partner_fee_amount,
+                        after_partner_fees,
+                    )
+                }
+            };
+        let after_slippage =
+            apply_slippage(after_partner_fees, quote.kind, slippage_bps).ok_or_else(overflow)?;
+
+        Ok(Self {
+            kind: quote.kind,
+            network_fee_amount,
+            partner_fee_amount,
+            partner_fee_bps,
+            before_network_costs,
+            after_network_costs,
+            after_partner_fees,
+            after_slippage,
+        })
+    }
+
+    /// Breakdown using the partner fee of the order's app data.
+    pub fn with_app_data(
+        quote: &OrderQuote,
+        app_data: &AppDataDocument,
+        slippage_bps: u32,
+    ) -> Result<Self> {
+        Self::new(quote, app_data.partner_fee_volume_bps(), slippage_bps)
+    }
+
+    /// The order to sign for the quote. Network costs and partner fees are
+    /// taken from the surplus, so the signed fee amount is zero.
+    pub fn order_data(&self, quote: &OrderQuote) -> OrderData {
+        OrderData {
+            sell_amount: self.after_slippage.sell_amount,
+            buy_amount: self.after_slippage.buy_amount,
+            fee_amount: U256::ZERO,
+            ..quote.order_data()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::Address;
+
+    use super::*;
+    use crate::primitives::{app_data::AppDataHash, partner_fee::PartnerFee};
+
+    fn quote(kind: OrderKind) -> OrderQuote {
+        OrderQuote {
+            sell_token: Address::repeat_byte(1),
+            buy_token: Address::repeat_byte(2),
+            receiver: None,
+            sell_amount: U256::from(990_000),
+            buy_amount: U256::from(1_980_000),
+            valid_to: 1_700_000_000,
+            app_data: AppDataHash::default().to_string(),
+            app_data_hash: None,
+            fee_amount: U256::from(10_000),
+            kind,
+            partially_fillable: false,
+            sell_token_balance: Default::default(),
+            buy_token_balance: Default::default(),
+            signing_scheme: "eip712".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_sell_order_fees_reduce_buy_amount() {
+        let quote = quote(OrderKind::Sell);
+
+        let amounts = QuoteAmountsAndCosts::new(&quote, 100, 50).unwrap();
+
+        assert_eq!(amounts.before_network_costs.buy_amount, U256::from(2_000_000));
+        assert_eq!(amounts.partner_fee_amount, U256::from(20_000));
+        assert_eq!(amounts.after_partner_fees.buy_amount, U256::from(1_960_000));
+        assert_eq!(amounts.after_slippage.buy_amount, U256::from(1_950_200));
+
+        let order = amounts.order_data(&quote);
+        assert_eq!(order.sell_amount, U256::from(1_000_000));
+        assert_eq!(order.buy_amount, U256::from(1_950_200));
+        assert_eq!(order.fee_amount, U256::ZERO);
+    }
+
+    #[test]
+    fn test_buy_order_fees_increase_sell_amount() {
+        let quote = quote(OrderKind::Buy);
+
+        let amounts = QuoteAmountsAndCosts::new(&quote, 100, 50).unwrap();
+
+        assert_eq!(amounts.after_network_costs.sell_amount, U256::from(1_000_000));
+        assert_eq!(amounts.partner_fee_amount, U256::from(9_900));
+        // Rounded up, the order may sell slightly more
+        assert_eq!(amounts.after_slippage.sell_amount, U256::from(1_014_950));
+        assert_eq!(amounts.order_data(&quote).buy_amount, quote.buy_amount);
+    }
+
+    #[test]
+    fn test_partner_fee_from_app_data() {
+        let app_data =
+            AppDataDocument::new("CoW Swap").partner_fee(PartnerFee::volume(100, Address::ZERO));
+
+        let amounts =
+            QuoteAmountsAndCosts::with_app_data(&quote(OrderKind::Sell), &app_data, 0).unwrap();
+
+        assert_eq!(amounts.partner_fee_bps, 100);
+        assert_eq!(amounts.after_slippage.buy_amount, U256::from(1_960_000));
+    }
+
+    #[test]
+    fn test_rejects_fee_above_100_percent() {
+        assert!(QuoteAmountsAndCosts::new(&quote(OrderKind::Sell), 10_001, 0).is_err());
+    }
+
+    #[test]
+    fn test_rejects_overflowing_amounts() {
+        let sell = OrderQuote { sell_amount: U256::MAX, ..quote(OrderKind::Sell) };
+        let buy = OrderQuote { sell_amount: U256::MAX - U256::from(1), ..quote(OrderKind::Buy) };
+
+        assert!(QuoteAmountsAndCosts::new(&sell, 0, 0).is_err());
+        assert!(QuoteAmountsAndCosts::new(&buy, 0, 0).is_err());
+    }
+}
@@ -0,0 +1,246 @@
+//! Client for the CoW Protocol subgraph, answering aggregate questions about
+//! historical protocol data that the orderbook API does not.
+
+pub mod models;
+
+use alloy::primitives::Address;
+use eyre::{Result, WrapErr, eyre};
+use models::{PeriodTotals, Settlement, SubgraphTrade, TokenStats, Totals, UserStats};
+use reqwest::Client;
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
+use reqwest_retry::{RetryTransientMiddleware, policies::ExponentialBackoff};
+use serde::{Deserialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+use tracing::{debug, error, info, trace};
+use url::Url;
+
+use crate::{config::Network, parsing::parse_response_body};
+
+const TOTALS_QUERY: &str = "query Totals {
+  totals {
+    tokens orders traders settlements volumeUsd volumeEth feesUsd feesEth
+  }
+}";
+
+const DAILY_TOTALS_QUERY: &str = "query DailyTotals($first: Int!) {
+  periods: dailyTotals(orderBy: timestamp, orderDirection: desc, first: $first) {
+    timestamp orders settlements volumeUsd volumeEth feesUsd feesEth
+  }
+}";
+
+const HOURLY_TOTALS_QUERY: &str = "query HourlyTotals($first: Int!) {
+  periods: hourlyTotals(orderBy: timestamp, orderDirection: desc, first: $first) {
+    timestamp orders settlements volumeUsd volumeEth feesUsd feesEth
+  }
+}";
+
+const TOKEN_FIELDS: &str = "address name symbol decimals totalVolume numberOfTrades \
+                            totalVolumeUsd totalVolumeEth priceUsd priceEth";
+
+const SETTLEMENTS_QUERY: &str = "query Settlements($first: Int!) {
+  settlements(orderBy: firstTradeTimestamp, orderDirection: desc, first: $first) {
+    txHash firstTradeTimestamp solver { address }
+  }
+}";
+
+const USER_QUERY: &str = "query User($id: ID!) {
+  user(id: $id) {
+    address firstTradeTimestamp numberOfTrades solvedAmountUsd solvedAmountEth
+  }
+}";
+
+const USER_TRADES_QUERY: &str = "query UserTrades($owner: String!, $first: Int!) {
+  trades(
+    where: { order_: { owner: $owner } }
+    orderBy: timestamp
+    orderDirection: desc
+    first: $first
+  ) {
+    timestamp txHash sellAmount buyAmount sellAmountUsd buyAmountUsd
+    sellToken { address symbol }
+    buyToken { address symbol }
+  }
+}";
+
+/// GraphQL response envelope.
+#[derive(Debug, Deserialize)]
+struct GraphQlResponse<T> {
+    data: Option<T>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+/// Client for the CoW Protocol subgraph of a network.
+#[derive(Debug)]
+pub struct SubgraphClient {
+    client: ClientWithMiddleware,
+    endpoint: Url,
+}
+
+impl SubgraphClient {
+    /// Client for the subgraph of `network` served by The Graph's gateway.
+    pub fn new(network: Network, api_key: &str) -> Result<Self> {
+        let subgraph_id = network
+            .subgraph_id()
+            .ok_or_else(|| eyre!("No subgraph deployed for network {}", network))?;
+        info!("Creating new SubgraphClient for network: {:?}", network);
+        Self::with_endpoint(&format!(
+            "https://gateway.thegraph.com/api/{}/subgraphs/id/{}",
+            api_key, subgraph_id
+        ))
+    }
+
+    /// Client for a subgraph served at `endpoint`, e.g. a self-hosted node.
+    pub fn with_endpoint(endpoint: &str) -> Result<Self> {
+        let endpoint = Url::parse(endpoint).wrap_err("Invalid subgraph endpoint")?;
+        let retry_policy = ExponentialBackoff::builder().build_with_max_retries(3);
+        let client = ClientBuilder::new(Client::new())
+            .with(RetryTransientMiddleware::new_with_policy(retry_policy))
+            .build();
+        Ok(Self { client, endpoint })
+    }
+
+    /// Runs a GraphQL query, returning its `data`.
+    pub async fn query<T: DeserializeOwned>(&self, query: &str, variables: Value) -> Result<T> {
+        trace!("Sending subgraph query: {}", query);
+        let body = json!({ "query": query, "variables": variables }).to_string();
+        let response = self
+            .client
+            .post(self.endpoint.clone())
+            .header("Content-Type", "application/json")
+            .body(body)
+            .send()
+            .await
+            .wrap_err("Failed to send subgraph query")?;
+
+        let status = response.status();
+        let body_text = response.text().await.wrap_err("Failed to extract response body text")?;
+        if !status.is_success() {
+            error!("HTTP Error {}: {}", status, body_text);
+            return Err(eyre!("HTTP Error {}: {}", status, body_text));
+        }
+
+        let response: GraphQlResponse<T> = parse_response_body(&body_text)?;
+        if !response.errors.is_empty() {
+            let messages: Vec<_> = response.errors.into_iter().map(|err| err.message).collect();
+            return Err(eyre!("Subgraph query failed: {}", messages.join("; ")));
+        }
+        debug!("Subgraph query succeeded");
+        response.data.ok_or_else(|| eyre!("Subgraph response has no data"))
+    }
+
+    /// Protocol-wide totals.
+    pub async fn totals(&self) -> Result<Totals> {
+        #[derive(Deserialize)]
+        struct Data {
+            totals: Vec<Totals>,
+        }
+        let data: Data = self.query(TOTALS_QUERY, json!({})).await?;
+        data.totals.into_iter().next().ok_or_else(|| eyre!("Subgraph has no totals"))
+    }
+
+    /// Totals of the last `days` days, newest first.
+    pub async fn daily_totals(&self, days: u32) -> Result<Vec<PeriodTotals>> {
+        self.periods(DAILY_TOTALS_QUERY, days).await
+    }
+
+    /// Totals of the last `hours` hours, newest first.
+    pub async fn hourly_totals(&self, hours: u32) -> Result<Vec<PeriodTotals>> {
+        self.periods(HOURLY_TOTALS_QUERY, hours).await
+    }
+
+    async fn periods(&self, query: &str, first: u32) -> Result<Vec<PeriodTotals>> {
+        #[derive(Deserialize)]
+        struct Data {
+            periods: Vec<PeriodTotals>,
+        }
+        let data: Data = self.query(query, json!({ "first": first })).await?;
+        Ok(data.periods)
+    }
+
+    /// The `first` tokens with the most USD volume.
+    pub async fn top_tokens(&self, first: u32) -> Result<Vec<TokenStats>> {
+        #[derive(Deserialize)]
+        struct Data {
+            tokens: Vec<TokenStats>,
+        }
+        let query = format!(
+            "query TopTokens($first: Int!) {{
+  tokens(orderBy: totalVolumeUsd, orderDirection: desc, first: $first) {{ {} }}
+}}",
+            TOKEN_FIELDS
+        );
+        let data: Data = self.query(&query, json!({ "first": first })).await?;
+        Ok(data.tokens)
+    }
+
+    /// Statistics of a token, if it was ever traded.
+    pub async fn token(&self, address: Address) -> Result<Option<TokenStats>> {
+        #[derive(Deserialize)]
+        struct Data {
+            token: Option<TokenStats>,
+        }
+        let query = format!("query Token($id: ID!) {{ token(id: $id) {{ {} }} }}", TOKEN_FIELDS);
+        let data: Data = self.query(&query, json!({ "id": entity_id(address) })).await?;
+        Ok(data.token)
+    }
+
+    /// The `first` most recent settlements.
+    pub async fn settlements(&self, first: u32) -> Result<Vec<Settlement>> {
+        #[derive(Deserialize)]
+        struct Data {
+            settlements: Vec<Settlement>,
+        }
+        let data: Data = self.query(SETTLEMENTS_QUERY, json!({ "first": first })).await?;
+        Ok(data.settlements)
+    }
+
+    /// Statistics of a user, if they ever traded.
+    pub async fn user(&self, address: Address) -> Result<Option<UserStats>> {
+        #[derive(Deserialize)]
+        struct Data {
+            user: Option<UserStats>,
+        }
+        let data: Data = self.query(USER_QUERY, json!({ "id": entity_id(address) })).await?;
+        Ok(data.user)
+    }
+
+    /// The `first` most recent trades of a user.
+    pub async fn user_trades(&self, address: Address, first: u32) -> Result<Vec<SubgraphTrade>> {
+        #[derive(Deserialize)]
+        struct Data {
+            trades: Vec<SubgraphTrade>,
+        }
+        let variables = json!({ "owner": entity_id(address), "first": first });
+        let data: Data = self.query(USER_TRADES_QUERY, variables).await?;
+        Ok(data.trades)
+    }
+}
+
+/// Subgraph entity ID of an address, its lowercase hex.
+fn entity_id(address: Address) -> String {
+    format!("{:#x}", address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entity_id_is_lowercase() {
+        let address: Address = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".parse().unwrap();
+
+        assert_eq!(entity_id(address), "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2");
+    }
+
+    #[test]
+    fn test_no_subgraph_for_base() {
+        assert!(SubgraphClient::new(Network::Base, "key").is_err());
+        assert!(SubgraphClient::new(Network::Mainnet, "key").is_ok());
+    }
+}
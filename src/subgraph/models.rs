@@ -0,0 +1,122 @@
+use alloy::primitives::{Address, TxHash, U256};
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+
+/// Protocol-wide totals since launch. Amounts are `BigDecimal`s, missing when
+/// no price was available.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Totals {
+    #[serde_as(as = "DisplayFromStr")]
+    pub tokens: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub orders: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub traders: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub settlements: u64,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub volume_usd: Option<f64>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub volume_eth: Option<f64>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub fees_usd: Option<f64>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub fees_eth: Option<f64>,
+}
+
+/// Totals over a day or an hour starting at `timestamp`.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeriodTotals {
+    pub timestamp: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub orders: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub settlements: u64,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub volume_usd: Option<f64>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub volume_eth: Option<f64>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub fees_usd: Option<f64>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub fees_eth: Option<f64>,
+}
+
+/// Trading statistics of a token.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenStats {
+    pub address: Address,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    /// Volume in token atoms.
+    pub total_volume: U256,
+    pub number_of_trades: u64,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub total_volume_usd: Option<f64>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub total_volume_eth: Option<f64>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub price_usd: Option<f64>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub price_eth: Option<f64>,
+}
+
+/// A settlement transaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Settlement {
+    pub tx_hash: TxHash,
+    pub first_trade_timestamp: u64,
+    pub solver: Option<AccountRef>,
+}
+
+/// Reference to a user or solver entity.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountRef {
+    pub address: Address,
+}
+
+/// Trading statistics of a user.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserStats {
+    pub address: Address,
+    pub first_trade_timestamp: u64,
+    pub number_of_trades: u64,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub solved_amount_usd: Option<f64>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub solved_amount_eth: Option<f64>,
+}
+
+/// A trade from a user's history.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubgraphTrade {
+    pub timestamp: u64,
+    pub tx_hash: TxHash,
+    pub sell_token: TokenRef,
+    pub buy_token: TokenRef,
+    pub sell_amount: U256,
+    pub buy_amount: U256,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub sell_amount_usd: Option<f64>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub buy_amount_usd: Option<f64>,
+}
+
+/// Reference to a token entity.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenRef {
+    pub address: Address,
+    pub symbol: String,
+}
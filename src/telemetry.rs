@@ -0,0 +1,20 @@
+//! Export of the spans of this crate, such as the `order_api_request` span of
+//! every Order API call, to OpenTelemetry.
+
+use opentelemetry::trace::Tracer;
+use tracing::{Level, Subscriber};
+use tracing_opentelemetry::PreSampledTracer;
+use tracing_subscriber::{Layer, filter::Targets, registry::LookupSpan};
+
+/// Layer exporting the spans of this crate with `tracer`, e.g. one of an OTLP
+/// tracer provider. Spans of other crates are left to the other layers of the
+/// subscriber.
+pub fn layer<S, T>(tracer: T) -> impl Layer<S>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+    T: Tracer + PreSampledTracer + Send + Sync + 'static,
+{
+    tracing_opentelemetry::layer()
+        .with_tracer(tracer)
+        .with_filter(Targets::new().with_target(env!("CARGO_CRATE_NAME"), Level::TRACE))
+}
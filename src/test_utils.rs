@@ -0,0 +1,27 @@
+//! Fixtures shared by the unit tests of several modules.
+
+use alloy::primitives::{Address, U256};
+
+#[cfg(feature = "export")]
+use crate::models::trade::Trade;
+use crate::primitives::order_data::{OrderData, OrderKind};
+
+/// Order of `sell_amount` token 0x01.. for `buy_amount` token 0x02.., never
+/// expiring.
+pub fn order_data(kind: OrderKind, sell_amount: u64, buy_amount: u64) -> OrderData {
+    OrderData {
+        sell_token: Address::repeat_byte(1),
+        buy_token: Address::repeat_byte(2),
+        sell_amount: U256::from(sell_amount),
+        buy_amount: U256::from(buy_amount),
+        valid_to: u32::MAX,
+        kind,
+        ..Default::default()
+    }
+}
+
+/// Trade of 1001 USDC for 0.51 WETH, shared with the integration tests.
+#[cfg(feature = "export")]
+pub fn trade() -> Trade {
+    serde_json::from_str(include_str!("../tests/common/trade.json")).unwrap()
+}
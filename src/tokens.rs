@@ -0,0 +1,259 @@
+//! Token metadata, resolved from token lists or on-chain, for displaying
+//! amounts in whole tokens.
+
+use std::{
+    collections::HashMap,
+    fs,
+    future::IntoFuture,
+    path::Path,
+    sync::{Mutex, PoisonError},
+};
+
+use alloy::{
+    primitives::{Address, U256, address},
+    providers::Provider,
+};
+use eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::{
+    config::Network,
+    contracts::IERC20Metadata,
+    math::format_units,
+    models::{order::Order, trade::Trade},
+};
+
+/// Placeholder address of the chain's native token, e.g. as buy token of
+/// orders receiving ETH.
+pub const NATIVE_TOKEN: Address = address!("0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE");
+
+/// Metadata of an ERC-20 token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenInfo {
+    pub chain_id: u64,
+    pub address: Address,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+/// Token list in the Uniswap format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenList {
+    pub name: String,
+    pub tokens: Vec<TokenInfo>,
+}
+
+impl TokenList {
+    /// Reads a token list JSON file.
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .wrap_err_with(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .wrap_err_with(|| format!("Failed to parse token list {}", path.display()))
+    }
+}
+
+/// Sell and buy amounts formatted in whole tokens, e.g. `1.5 WETH`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FormattedAmounts {
+    pub sell: String,
+    pub buy: String,
+}
+
+/// Resolves and caches the metadata of the tokens of a network.
+#[derive(Debug)]
+pub struct TokenRegistry<P> {
+    provider: P,
+    network: Network,
+    tokens: Mutex<HashMap<Address, TokenInfo>>,
+}
+
+impl<P: Provider> TokenRegistry<P> {
+    pub fn new(provider: P, network: Network) -> Self {
+        Self { provider, network, tokens: Mutex::default() }
+    }
+
+    /// Adds the tokens of `list` on this registry's network, returning how
+    /// many were added.
+    pub fn add_token_list(&self, list: &TokenList) -> usize {
+        let chain_id = self.network.chain_id();
+        let mut tokens = self.tokens.lock().unwrap_or_else(PoisonError::into_inner);
+        let before = tokens.len();
+        for token in list.tokens.iter().filter(|token| token.chain_id == chain_id) {
+            tokens.entry(token.address).or_insert_with(|| token.clone());
+        }
+        debug!("Added {} tokens from list {}", tokens.len() - before, list.name);
+        tokens.len() - before
+    }
+
+    /// Adds the tokens of a token list JSON file.
+    pub fn load_token_list(&self, path: &Path) -> Result<usize> {
+        Ok(self.add_token_list(&TokenList::read(path)?))
+    }
+
+    /// Adds or overrides the metadata of a token.
+    pub fn insert(&self, token: TokenInfo) {
+        self.tokens.lock().unwrap_or_else(PoisonError::into_inner).insert(token.address, token);
+    }
+
+    /// Metadata of `address`, from the cache or read from the token contract.
+    pub async fn token(&self, address: Address) -> Result<TokenInfo> {
+        if let Some(token) = self.cached(address) {
+            return Ok(token);
+        }
+        let token =
+            if address == NATIVE_TOKEN { self.native_token() } else { self.fetch(address).await? };
+        self.insert(token.clone());
+        Ok(token)
+    }
+
+    /// Formats an amount of `token` atoms in whole tokens, e.g. `1.5 WETH`.
+    pub async fn format_amount(&self, token: Address, amount: U256) -> Result<String> {
+        let token = self.token(token).await?;
+        Ok(format!("{} {}", format_units(amount, token.decimals), token.symbol))
+    }
+
+    /// Signed amounts of an order.
+    pub async fn format_order(&self, order: &Order) -> Result<FormattedAmounts> {
+        self.format_amounts(order.sell_token, order.sell_amount, order.buy_token, order.buy_amount)
+            .await
+    }
+
+    /// Executed amounts of a trade.
+    pub async fn format_trade(&self, trade: &Trade) -> Result<FormattedAmounts> {
+        self.format_amounts(trade.sell_token, trade.sell_amount, trade.buy_token, trade.buy_amount)
+            .await
+    }
+
+    async fn format_amounts(
+        &self,
+        sell_token: Address,
+        sell_amount: U256,
+        buy_token: Address,
+        buy_amount: U256,
+    ) -> Result<FormattedAmounts> {
+        Ok(FormattedAmounts {
+            sell: self.format_amount(sell_token, sell_amount).await?,
+            buy: self.format_amount(buy_token, buy_amount).await?,
+        })
+    }
+
+    fn cached(&self, address: Address) -> Option<TokenInfo> {
+        self.tokens.lock().unwrap_or_else(PoisonError::into_inner).get(&address).cloned()
+    }
+
+    fn native_token(&self) -> TokenInfo {
+        let (name, symbol) = match self.network {
+            Network::Gnosis | Network::GnosisStaging => ("xDAI", "xDAI"),
+            _ => ("Ether", "ETH"),
+        };
+        TokenInfo {
+            chain_id: self.network.chain_id(),
+            address: NATIVE_TOKEN,
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+            decimals: 18,
+        }
+    }
+
+    /// Reads the metadata from the token contract. Only `decimals` is
+    /// required, as some tokens do not implement `name` and `symbol` as
+    /// strings.
+    async fn fetch(&self, address: Address) -> Result<TokenInfo> {
+        debug!("Reading metadata of token {}", address);
+        let contract = IERC20Metadata::new(address, &self.provider);
+        let decimals = contract.decimals();
+        let symbol = contract.symbol();
+        let name = contract.name();
+        let (decimals, symbol, name) = futures::join!(
+            decimals.call().into_future(),
+            symbol.call().into_future(),
+            name.call().into_future()
+        );
+
+        let decimals =
+            decimals.wrap_err_with(|| format!("Failed to get decimals of {}", address))?._0;
+        let symbol = symbol.map(|symbol| symbol._0).unwrap_or_else(|err| {
+            warn!("Failed to get symbol of {}: {}", address, err);
+            address.to_string()
+        });
+        let name = name.map(|name| name._0).unwrap_or_else(|err| {
+            warn!("Failed to get name of {}: {}", address, err);
+            String::new()
+        });
+        Ok(TokenInfo { chain_id: self.network.chain_id(), address, name, symbol, decimals })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::providers::ProviderBuilder;
+
+    use super::*;
+
+    const TOKEN_LIST: &str = r#"{
+        "name": "Test List",
+        "timestamp": "2024-01-01T00:00:00Z",
+        "version": { "major": 1, "minor": 0, "patch": 0 },
+        "tokens": [
+            {
+                "chainId": 1,
+                "address": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+                "name": "USD Coin",
+                "symbol": "USDC",
+                "decimals": 6,
+                "logoURI": "https://example.com/usdc.png"
+            },
+            {
+                "chainId": 100,
+                "address": "0xDDAfbb505ad214D7b80b1f830fcCc89B60fb7A83",
+                "name": "USD Coin on xDai",
+                "symbol": "USDC",
+                "decimals": 6
+            }
+        ]
+    }"#;
+
+    fn registry() -> TokenRegistry<impl Provider> {
+        registry_on(Network::Mainnet)
+    }
+
+    fn registry_on(network: Network) -> TokenRegistry<impl Provider> {
+        // Never called, as the tests only use cached tokens
+        let provider = ProviderBuilder::new().on_http("http://localhost:1".parse().unwrap());
+        TokenRegistry::new(provider, network)
+    }
+
+    #[tokio::test]
+    async fn test_token_list_filters_network() {
+        let registry = registry();
+        let list: TokenList = serde_json::from_str(TOKEN_LIST).unwrap();
+
+        assert_eq!(registry.add_token_list(&list), 1);
+        let usdc = registry.token(address!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48")).await;
+        assert_eq!(usdc.unwrap().symbol, "USDC");
+    }
+
+    #[tokio::test]
+    async fn test_format_amount() {
+        let registry = registry();
+        registry.add_token_list(&serde_json::from_str(TOKEN_LIST).unwrap());
+
+        let usdc = address!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+        let amount = registry.format_amount(usdc, U256::from(1_500_000)).await.unwrap();
+        let eth = registry.format_amount(NATIVE_TOKEN, U256::from(10).pow(U256::from(17))).await;
+
+        assert_eq!(amount, "1.5 USDC");
+        assert_eq!(eth.unwrap(), "0.1 ETH");
+    }
+
+    #[test]
+    fn test_native_token_of_gnosis_staging() {
+        assert_eq!(registry_on(Network::GnosisStaging).native_token().symbol, "xDAI");
+        assert_eq!(registry_on(Network::SepoliaStaging).native_token().symbol, "ETH");
+    }
+}
@@ -0,0 +1,205 @@
+//! Pre-flight checks of orders against chain state, catching problems the
+//! orderbook would otherwise only report when rejecting the order.
+
+use std::fmt;
+
+use alloy::{
+    eips::BlockNumberOrTag,
+    primitives::{Address, U256},
+    providers::Provider,
+};
+use eyre::{Result, WrapErr, eyre};
+use tracing::debug;
+
+use crate::{
+    config::Network,
+    contracts::IERC20,
+    primitives::order_data::{OrderData, SellTokenSource},
+};
+
+/// A problem preventing an order from being accepted or settled.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum OrderProblem {
+    ZeroSellAmount,
+    ZeroBuyAmount,
+    SameSellAndBuyToken,
+    /// The sell amount plus the fee amount does not fit in 256 bits.
+    AmountOverflow,
+    /// The order expired before the latest block.
+    Expired {
+        valid_to: u32,
+        block_timestamp: u64,
+    },
+    /// The receiver is a protocol contract or one of the traded tokens, which
+    /// would lose the bought tokens.
+    InvalidReceiver(Address),
+    InsufficientBalance {
+        balance: U256,
+        required: U256,
+    },
+    InsufficientAllowance {
+        allowance: U256,
+        required: U256,
+    },
+}
+
+impl fmt::Display for OrderProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderProblem::ZeroSellAmount => write!(f, "sell amount is zero"),
+            OrderProblem::ZeroBuyAmount => write!(f, "buy amount is zero"),
+            OrderProblem::SameSellAndBuyToken => write!(f, "sell and buy tokens are the same"),
+            OrderProblem::AmountOverflow => write!(f, "sell amount plus fee amount overflows"),
+            OrderProblem::Expired { valid_to, block_timestamp } => write!(
+                f,
+                "order expired at {} before latest block at {}",
+                valid_to, block_timestamp
+            ),
+            OrderProblem::InvalidReceiver(receiver) => write!(f, "invalid receiver {}", receiver),
+            OrderProblem::InsufficientBalance { balance, required } => {
+                write!(f, "insufficient balance {} for required {}", balance, required)
+            }
+            OrderProblem::InsufficientAllowance { allowance, required } => write!(
+                f,
+                "insufficient vault relayer allowance {} for required {}",
+                allowance, required
+            ),
+        }
+    }
+}
+
+/// Checks that do not need chain state.
+pub fn static_problems(order: &OrderData, network: &Network) -> Vec<OrderProblem> {
+    let mut problems = Vec::new();
+    if order.sell_amount.is_zero() {
+        problems.push(OrderProblem::ZeroSellAmount);
+    }
+    if order.buy_amount.is_zero() {
+        problems.push(OrderProblem::ZeroBuyAmount);
+    }
+    if order.sell_token == order.buy_token {
+        problems.push(OrderProblem::SameSellAndBuyToken);
+    }
+    if order.sell_amount.checked_add(order.fee_amount).is_none() {
+        problems.push(OrderProblem::AmountOverflow);
+    }
+    let invalid_receivers =
+        [network.settlement_contract(), network.vault_relayer(), order.sell_token, order.buy_token];
+    if invalid_receivers.contains(&order.receiver) {
+        problems.push(OrderProblem::InvalidReceiver(order.receiver));
+    }
+    problems
+}
+
+/// Validates orders against the state of the chain of a network.
+#[derive(Debug)]
+pub struct OrderValidator<P> {
+    provider: P,
+    network: Network,
+}
+
+impl<P: Provider> OrderValidator<P> {
+    pub fn new(provider: P, network: Network) -> Self {
+        Self { provider, network }
+    }
+
+    /// Returns every problem found with `order` placed by `owner`, an empty
+    /// list meaning the order can be posted.
+    ///
+    /// Balance and allowance are only checked for orders selling from ERC-20
+    /// balances. Partially fillable orders only need a non-zero balance and
+    /// allowance.
+    pub async fn validate(&self, order: &OrderData, owner: Address) -> Result<Vec<OrderProblem>> {
+        let mut problems = static_problems(order, &self.network);
+
+        let block = self
+            .provider
+            .get_block_by_number(BlockNumberOrTag::Latest)
+            .await
+            .wrap_err("Failed to get latest block")?
+            .ok_or_else(|| eyre!("Latest block not found"))?;
+        if u64::from(order.valid_to) <= block.header.timestamp {
+            problems.push(OrderProblem::Expired {
+                valid_to: order.valid_to,
+                block_timestamp: block.header.timestamp,
+            });
+        }
+
+        if order.sell_token_balance != SellTokenSource::Erc20 {
+            debug!("Skipping balance checks for {} balance", order.sell_token_balance);
+            return Ok(problems);
+        }
+
+        let required = if order.partially_fillable {
+            U256::from(1)
+        } else {
+            match order.sell_amount.checked_add(order.fee_amount) {
+                Some(required) => required,
+                // Reported as a static problem, no balance can cover it
+                None => return Ok(problems),
+            }
+        };
+        let token = IERC20::new(order.sell_token, &self.provider);
+        let balance = token
+            .balanceOf(owner)
+            .call()
+            .await
+            .wrap_err_with(|| format!("Failed to get balance of {}", owner))?
+            ._0;
+        if balance < required {
+            problems.push(OrderProblem::InsufficientBalance { balance, required });
+        }
+        let allowance = token
+            .allowance(owner, self.network.vault_relayer())
+            .call()
+            .await
+            .wrap_err_with(|| format!("Failed to get allowance of {}", owner))?
+            ._0;
+        if allowance < required {
+            problems.push(OrderProblem::InsufficientAllowance { allowance, required });
+        }
+
+        Ok(problems)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{primitives::order_data::OrderKind, test_utils::order_data};
+
+    #[test]
+    fn test_static_problems_valid_order() {
+        assert!(static_problems(&order_data(OrderKind::Sell, 1, 1), &Network::Mainnet).is_empty());
+    }
+
+    #[test]
+    fn test_static_problems_invalid_order() {
+        let order = OrderData {
+            buy_token: Address::repeat_byte(1),
+            sell_amount: U256::ZERO,
+            receiver: Network::Mainnet.settlement_contract(),
+            ..order_data(OrderKind::Sell, 1, 1)
+        };
+
+        assert_eq!(
+            static_problems(&order, &Network::Mainnet),
+            vec![
+                OrderProblem::ZeroSellAmount,
+                OrderProblem::SameSellAndBuyToken,
+                OrderProblem::InvalidReceiver(Network::Mainnet.settlement_contract()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_static_problems_amount_overflow() {
+        let order = OrderData {
+            sell_amount: U256::MAX,
+            fee_amount: U256::from(1),
+            ..order_data(OrderKind::Sell, 1, 1)
+        };
+
+        assert_eq!(static_problems(&order, &Network::Mainnet), vec![OrderProblem::AmountOverflow]);
+    }
+}
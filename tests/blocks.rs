@@ -0,0 +1,67 @@
+mod common;
+
+use alloy::providers::ProviderBuilder;
+use common::stub;
+use cow_sdk::{blocks::BlockTimestamps, models::trade::Trade};
+use eyre::Result;
+use serde_json::{Value, json};
+
+fn trade(block_number: u64) -> Trade {
+    Trade { block_number, ..common::trade() }
+}
+
+/// Answers a batch of `eth_getBlockByNumber` calls with blocks 12 seconds
+/// apart.
+fn blocks(request: &Value) -> String {
+    let responses: Vec<Value> = request
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|call| {
+            let number = &call["params"][0];
+            let block =
+                u64::from_str_radix(number.as_str().unwrap().trim_start_matches("0x"), 16).unwrap();
+            json!({
+                "jsonrpc": "2.0",
+                "id": call["id"],
+                "result": {
+                    "hash": format!("0x{:064x}", block),
+                    "parentHash": format!("0x{:064x}", block - 1),
+                    "sha3Uncles": format!("0x{}", "00".repeat(32)),
+                    "miner": format!("0x{}", "00".repeat(20)),
+                    "stateRoot": format!("0x{}", "00".repeat(32)),
+                    "transactionsRoot": format!("0x{}", "00".repeat(32)),
+                    "receiptsRoot": format!("0x{}", "00".repeat(32)),
+                    "logsBloom": format!("0x{}", "00".repeat(256)),
+                    "difficulty": "0x0",
+                    "number": number,
+                    "gasLimit": "0x1c9c380",
+                    "gasUsed": "0x0",
+                    "timestamp": format!("{:#x}", 1_700_000_000 + block * 12),
+                    "extraData": "0x",
+                    "mixHash": format!("0x{}", "00".repeat(32)),
+                    "nonce": "0x0000000000000000",
+                    "uncles": [],
+                    "transactions": []
+                }
+            })
+        })
+        .collect();
+    Value::from(responses).to_string()
+}
+
+#[tokio::test]
+async fn test_annotate_trades_in_one_batch() -> Result<()> {
+    let (url, request) = stub("/", blocks);
+    let timestamps = BlockTimestamps::new(ProviderBuilder::new().on_http(url.parse()?));
+
+    let trades = timestamps.annotate(vec![trade(10), trade(11), trade(10)]).await?;
+
+    assert_eq!(trades[0].timestamp.timestamp(), 1_700_000_120);
+    assert_eq!(trades[1].timestamp.timestamp(), 1_700_000_132);
+    assert_eq!(trades[2].timestamp, trades[0].timestamp);
+    assert_eq!(request.join().unwrap().as_array().unwrap().len(), 2);
+    // Cached, the stub only answers once
+    assert_eq!(timestamps.timestamp(11).await?, trades[1].timestamp);
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    thread,
+};
+
+use cow_sdk::models::trade::Trade;
+use serde_json::Value;
+
+/// Trade of 1001 USDC for 0.51 WETH, shared with the unit tests.
+#[allow(dead_code)]
+pub fn trade() -> Trade {
+    serde_json::from_str(include_str!("trade.json")).unwrap()
+}
+
+/// Answers a single JSON request on a local port with `respond`, returning
+/// the URL and a handle yielding the request body.
+pub fn stub(
+    path: &str,
+    respond: impl FnOnce(&Value) -> String + Send + 'static,
+) -> (String, thread::JoinHandle<Value>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}{}", listener.local_addr().unwrap(), path);
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':')
+                && name.eq_ignore_ascii_case("content-length")
+            {
+                content_length = value.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        let request = serde_json::from_slice(&body).unwrap();
+        let response = respond(&request);
+        write!(
+            reader.get_mut(),
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            response.len(),
+            response
+        )
+        .unwrap();
+        request
+    });
+    (url, handle)
+}
@@ -1,25 +1,45 @@
+use std::{env, path::Path, sync::Arc};
+
 use alloy::primitives::{Address, TxHash, U256};
-use chrono::Utc;
+use chrono::DateTime;
 use cow_sdk::{
     config::network::Network,
     models::order::{
         CompetitionOrderStatus, Interactions, Order, OrderCancellations, OrderStatus, PartialOrder,
     },
-    orderbook::{GetTradesQuery, OrderApiClient},
+    orderbook::{GetTradesQuery, OrderApiClient, recording::Recorder},
     primitives::{
         app_data::{AppData, AppDataHash, FullAppData},
         order_uid::OrderUid,
     },
 };
-use eyre::Result;
+use eyre::{Result, eyre};
 
 const ORDER_ID: &str = "0xeaef82ff8696bff255e130b266231acb53a8f02823ed89b33acda5fd3987a53ad8da6bf26964af9d7eed9e03e53415d37aa96045676d56da";
 const TX_HASH: &str = "0xffd92faa1419c59ff0ac7f090998e9159f4b7f28bf67ad6b061c728c0da265f2";
 
+/// Client replaying the fixture `tests/fixtures/orderbook/{fixture}.json`, or
+/// recording it from the live API when `COW_RECORD` is set, e.g. with
+/// `COW_RECORD=1 cargo test --test orderbook -- --include-ignored`.
+fn client(fixture: &str) -> Result<OrderApiClient> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/orderbook")
+        .join(format!("{}.json", fixture));
+    let recorder = if env::var_os("COW_RECORD").is_some() {
+        Recorder::record(path)
+    } else if path.exists() {
+        Recorder::replay(path)?
+    } else {
+        return Err(eyre!("Missing fixture {}, record it with COW_RECORD set", path.display()));
+    };
+    let recorder = recorder.redact_field("signature");
+    OrderApiClient::with_middleware(Network::Mainnet, Arc::new(recorder))
+}
+
 #[tokio::test]
 #[ignore]
 async fn test_get_order_by_id() -> Result<()> {
-    let client = OrderApiClient::new(Network::Mainnet)?;
+    let client = client("get_order_by_id")?;
     let order_id: OrderUid = ORDER_ID.parse()?;
     let order = client.get_order_by_id(&order_id).await?;
 
@@ -31,7 +51,7 @@ async fn test_get_order_by_id() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_get_order_by_tx_hash() -> Result<()> {
-    let client = OrderApiClient::new(Network::Mainnet)?;
+    let client = client("get_order_by_tx_hash")?;
     let tx_hash: TxHash = TX_HASH.parse()?;
 
     let orders = client.get_orders_by_tx_hash(&tx_hash).await?;
@@ -45,7 +65,7 @@ async fn test_get_order_by_tx_hash() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_get_order_status_by_id() -> Result<()> {
-    let client = OrderApiClient::new(Network::Mainnet)?;
+    let client = client("get_order_status_by_id")?;
     let order_id: OrderUid = ORDER_ID.parse()?;
 
     let response = client.get_order_status(&order_id).await?;
@@ -58,7 +78,7 @@ async fn test_get_order_status_by_id() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_get_user_orders() -> Result<()> {
-    let client = OrderApiClient::new(Network::Mainnet)?;
+    let client = client("get_user_orders")?;
     let address: Address = "0xd8da6bf26964af9d7eed9e03e53415d37aa96045".parse()?;
     let offset = 1;
     let limit = 1;
@@ -70,10 +90,51 @@ async fn test_get_user_orders() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+#[ignore]
+async fn test_get_user_orders_v2() -> Result<()> {
+    let client = client("get_user_orders_v2")?;
+    let address: Address = "0xd8da6bf26964af9d7eed9e03e53415d37aa96045".parse()?;
+
+    let response = client.get_user_orders_v2(&address, None, Some(1)).await?;
+
+    assert_eq!(response.len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_get_orders_by_ids() -> Result<()> {
+    let client = client("get_orders_by_ids")?;
+    let order_id: OrderUid = ORDER_ID.parse()?;
+    let unknown_id = OrderUid::new([0xff; 56].into());
+
+    let orders = client.get_orders_by_ids(&[order_id, unknown_id]).await?;
+
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].uid, order_id);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_get_order_debug() -> Result<()> {
+    let client = client("get_order_debug")?;
+    let order_id: OrderUid = ORDER_ID.parse()?;
+
+    if let Some(report) = client.get_order_debug(&order_id).await? {
+        assert_eq!(report.order_uid, order_id);
+    }
+
+    Ok(())
+}
+
 #[tokio::test]
 #[ignore]
 async fn test_get_trades_by_owner() -> Result<()> {
-    let client = OrderApiClient::new(Network::Mainnet)?;
+    let client = client("get_trades_by_owner")?;
     let address: Address = "0xd8da6bf26964af9d7eed9e03e53415d37aa96045".parse()?;
 
     let trades = client.get_trades(&GetTradesQuery::ByOwner(address)).await?;
@@ -86,7 +147,7 @@ async fn test_get_trades_by_owner() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_get_trades_by_order_id() -> Result<()> {
-    let client = OrderApiClient::new(Network::Mainnet)?;
+    let client = client("get_trades_by_order_id")?;
     let order_id: OrderUid = ORDER_ID.parse()?;
 
     let trades = client.get_trades(&GetTradesQuery::ByOrderId(order_id)).await?;
@@ -100,7 +161,7 @@ async fn test_get_trades_by_order_id() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_get_token_price() -> Result<()> {
-    let client = OrderApiClient::new(Network::Mainnet)?;
+    let client = client("get_token_price")?;
     let token_address: Address = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".parse()?;
 
     let response = client.get_token_price(&token_address).await?;
@@ -113,7 +174,7 @@ async fn test_get_token_price() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_get_competition_by_tx_hash() -> Result<()> {
-    let client = OrderApiClient::new(Network::Mainnet)?;
+    let client = client("get_competition_by_tx_hash")?;
     let tx_hash: TxHash = TX_HASH.parse()?;
 
     let response = client.get_competition_by_tx_hash(&tx_hash).await?;
@@ -127,7 +188,7 @@ async fn test_get_competition_by_tx_hash() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_get_latest_competition() -> Result<()> {
-    let client = OrderApiClient::new(Network::Mainnet)?;
+    let client = client("get_latest_competition")?;
 
     let response = client.get_latest_competition().await;
 
@@ -140,7 +201,7 @@ async fn test_get_latest_competition() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_get_competition_by_id() -> Result<()> {
-    let client = OrderApiClient::new(Network::Mainnet)?;
+    let client = client("get_competition_by_id")?;
     let auction_id = 1;
 
     let response = client.get_competition_by_id(&auction_id).await?;
@@ -153,7 +214,7 @@ async fn test_get_competition_by_id() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_get_api_version() -> Result<()> {
-    let client = OrderApiClient::new(Network::Mainnet)?;
+    let client = client("get_api_version")?;
 
     let response = client.get_version().await;
 
@@ -165,7 +226,7 @@ async fn test_get_api_version() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_get_total_surplus() -> Result<()> {
-    let client = OrderApiClient::new(Network::Mainnet)?;
+    let client = client("get_total_surplus")?;
     let address: Address = "0xd8da6bf26964af9d7eed9e03e53415d37aa96045".parse()?;
 
     let response = client.get_total_surplus(&address).await?;
@@ -178,7 +239,7 @@ async fn test_get_total_surplus() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_get_app_data() -> Result<()> {
-    let client = OrderApiClient::new(Network::Mainnet)?;
+    let client = client("get_app_data")?;
     let app_data_hash: AppDataHash =
         "0x00e421be3c3b0e20c582c0d803018c418b56ea61add1811bec2509e003a17b42".parse()?;
 
@@ -192,7 +253,7 @@ async fn test_get_app_data() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_create_order_with_invalid_order() -> Result<()> {
-    let client = OrderApiClient::new(Network::Mainnet)?;
+    let client = client("create_order_with_invalid_order")?;
     let order = Order {
         app_data: "0x".to_string(),
         available_balance: None,
@@ -200,7 +261,8 @@ async fn test_create_order_with_invalid_order() -> Result<()> {
         buy_token: Address::default(),
         buy_token_balance: "erc20".to_string(),
         class: "limit".to_string(),
-        creation_date: Utc::now(),
+        // Fixed, so that the request matches the recorded one
+        creation_date: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
         executed_buy_amount: U256::default(),
         executed_fee: U256::default(),
         executed_fee_amount: U256::default(),
@@ -244,7 +306,7 @@ async fn test_create_order_with_invalid_order() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_cancel_order() -> Result<()> {
-    let client = OrderApiClient::new(Network::Mainnet)?;
+    let client = client("cancel_order")?;
     let order_ids: Vec<OrderUid> = vec![ORDER_ID.parse()?];
     let cancellations = OrderCancellations {
         order_ids,
@@ -266,7 +328,7 @@ async fn test_cancel_order() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_get_quote() -> Result<()> {
-    let client = OrderApiClient::new(Network::Mainnet)?;
+    let client = client("get_quote")?;
     let partial_order = PartialOrder {
         app_data: "0x".to_string(),
         buy_amount: U256::default(),
@@ -299,7 +361,7 @@ async fn test_get_quote() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_upload_app_data() -> Result<()> {
-    let client = OrderApiClient::new(Network::Mainnet)?;
+    let client = client("upload_app_data")?;
     let app_data = AppData {
         full_app_data: FullAppData { version: "0.0.1".to_string(), metadata: "0x".to_string() },
     };
@@ -318,7 +380,7 @@ async fn test_upload_app_data() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_upload_app_data_by_hash() -> Result<()> {
-    let client = OrderApiClient::new(Network::Mainnet)?;
+    let client = client("upload_app_data_by_hash")?;
     let app_data = AppData {
         full_app_data: FullAppData { version: "0.0.1".to_string(), metadata: "0x".to_string() },
     };
@@ -335,3 +397,50 @@ async fn test_upload_app_data_by_hash() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(feature = "blocking")]
+#[test]
+#[ignore]
+fn test_blocking_get_order_by_id() -> Result<()> {
+    let client = cow_sdk::orderbook::blocking::OrderApiClient::new(Network::Mainnet)?;
+    let order_id: OrderUid = ORDER_ID.parse()?;
+    let order = client.get_order_by_id(&order_id)?;
+
+    assert_eq!(order.uid, order_id);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_get_user_orders_across_networks() -> Result<()> {
+    let client = cow_sdk::orderbook::multi_network::MultiNetworkClient::new([
+        Network::Mainnet,
+        Network::Gnosis,
+        Network::Arbitrum,
+        Network::Base,
+    ]);
+    let owner: Address = "0xd8da6bf26964af9d7eed9e03e53415d37aa96045".parse()?;
+
+    let orders = client.get_user_orders(&owner).await?;
+
+    assert!(orders.iter().any(|(network, _)| *network == Network::Mainnet));
+    assert!(orders.windows(2).all(|w| w[0].1.creation_date >= w[1].1.creation_date));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_find_order_in_prod_environment() -> Result<()> {
+    let client =
+        cow_sdk::orderbook::multi_network::MultiNetworkClient::environments(Network::Mainnet);
+    let order_id: OrderUid = ORDER_ID.parse()?;
+
+    let (network, order) = client.find_order(&order_id).await?.expect("order not found");
+
+    assert_eq!(network, Network::Mainnet);
+    assert_eq!(order.uid, order_id);
+
+    Ok(())
+}
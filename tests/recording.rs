@@ -0,0 +1,105 @@
+mod common;
+
+use std::{collections::BTreeMap, fs, sync::Arc};
+
+use common::stub;
+use cow_sdk::{
+    config::network::Network,
+    orderbook::{
+        OrderApiClient,
+        recording::{Body, Interaction, RecordedRequest, RecordedResponse, Recorder},
+    },
+};
+use eyre::Result;
+use reqwest::Client;
+use reqwest_middleware::ClientBuilder;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_record_then_replay() -> Result<()> {
+    let (url, request) =
+        stub("/api/v1/quote", |_| r#"{"quote":{"sellAmount":"1000"},"id":7}"#.to_string());
+    let url = format!("{}?apiKey=secret", url);
+    let path = std::env::temp_dir().join(format!("cow-sdk-recording-{}.json", std::process::id()));
+    let body = json!({ "sellToken": "0x01", "signature": "0x1234" }).to_string();
+
+    let recorder = Arc::new(Recorder::record(&path).redact_field("signature"));
+    let client = ClientBuilder::new(Client::new()).with_arc(recorder).build();
+    let recorded = client.post(&url).body(body.clone()).send().await?.text().await?;
+    request.join().unwrap();
+
+    let fixture = fs::read_to_string(&path)?;
+    assert!(!fixture.contains("secret"));
+    assert!(!fixture.contains("0x1234"));
+
+    // The stub only answers once, so this response comes from the fixture
+    let client = ClientBuilder::new(Client::new())
+        .with_arc(Arc::new(Recorder::replay(&path)?.redact_field("signature")))
+        .build();
+    let replayed = client.post(&url).body(body).send().await?.text().await?;
+    fs::remove_file(&path)?;
+
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&replayed)?,
+        serde_json::from_str::<serde_json::Value>(&recorded)?
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_order_api_client_replays_fixture() -> Result<()> {
+    let path =
+        std::env::temp_dir().join(format!("cow-sdk-replay-version-{}.json", std::process::id()));
+    let interaction = Interaction {
+        request: RecordedRequest {
+            method: "GET".to_string(),
+            url: "https://api.cow.fi/mainnet/api/v1/version".to_string(),
+            body: None,
+        },
+        response: RecordedResponse {
+            status: 200,
+            headers: BTreeMap::new(),
+            body: Body::Text("v2.0.0".to_string()),
+        },
+    };
+    fs::write(&path, serde_json::to_string(&[interaction])?)?;
+
+    let client =
+        OrderApiClient::with_middleware(Network::Mainnet, Arc::new(Recorder::replay(&path)?))?;
+    fs::remove_file(&path)?;
+
+    assert_eq!(client.get_version().await?, "v2.0.0");
+    let err = client.get_version().await.unwrap_err();
+    assert!(err.chain().any(|err| err.to_string().contains("No recorded response")));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_replayed_errors_are_not_retried() -> Result<()> {
+    let path =
+        std::env::temp_dir().join(format!("cow-sdk-replay-error-{}.json", std::process::id()));
+    let interaction = Interaction {
+        request: RecordedRequest {
+            method: "GET".to_string(),
+            url: "https://api.cow.fi/mainnet/api/v1/version".to_string(),
+            body: None,
+        },
+        response: RecordedResponse {
+            status: 503,
+            headers: BTreeMap::new(),
+            body: Body::Text("Service Unavailable".to_string()),
+        },
+    };
+    fs::write(&path, serde_json::to_string(&[interaction])?)?;
+
+    let client =
+        OrderApiClient::with_middleware(Network::Mainnet, Arc::new(Recorder::replay(&path)?))?;
+    fs::remove_file(&path)?;
+
+    // Retrying would find no second recorded response
+    assert_eq!(client.get_version().await?, "Service Unavailable");
+    let stats = &client.request_metrics().snapshot()["get_version"];
+    assert_eq!(stats.responses[&503], 1);
+    assert_eq!(stats.retries, 0);
+    Ok(())
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use alloy::primitives::{U256, address};
+use common::stub;
+use cow_sdk::{config::network::Network, subgraph::SubgraphClient};
+use eyre::Result;
+
+#[tokio::test]
+async fn test_totals_from_stub() -> Result<()> {
+    let (endpoint, request) = stub("/subgraph", |_| {
+        r#"{"data":{"totals":[{"tokens":"3210","orders":"1500000","traders":"80000",
+        "settlements":"900000","volumeUsd":"35000000000.5","volumeEth":null,
+        "feesUsd":"1200000.25","feesEth":"600.1"}]}}"#
+            .to_string()
+    });
+    let client = SubgraphClient::with_endpoint(&endpoint)?;
+
+    let totals = client.totals().await?;
+
+    assert_eq!(totals.orders, 1_500_000);
+    assert_eq!(totals.volume_usd, Some(35_000_000_000.5));
+    assert_eq!(totals.volume_eth, None);
+    assert!(request.join().unwrap()["query"].as_str().unwrap().contains("totals"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_user_trades_from_stub() -> Result<()> {
+    let (endpoint, request) = stub("/subgraph", |_| {
+        r#"{"data":{"trades":[{"timestamp":1700000000,
+        "txHash":"0x8d4f5a5a4b7ebc3b8c1c8b8f4bd3a3f3e9d9a8bfbbfa1fa0c2bd6a4bd2c8e1f0",
+        "sellAmount":"1000000000","buyAmount":"500000000000000000",
+        "sellAmountUsd":"1000","buyAmountUsd":null,
+        "sellToken":{"address":"0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48","symbol":"USDC"},
+        "buyToken":{"address":"0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2","symbol":"WETH"}}]}}"#
+            .to_string()
+    });
+    let client = SubgraphClient::with_endpoint(&endpoint)?;
+    let owner = address!("0x9008D19f58AAbD9eD0D60971565AA8510560ab41");
+
+    let trades = client.user_trades(owner, 10).await?;
+
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].sell_amount, U256::from(1_000_000_000));
+    assert_eq!(trades[0].buy_token.symbol, "WETH");
+    let request = request.join().unwrap();
+    assert_eq!(request["variables"]["owner"], "0x9008d19f58aabd9ed0d60971565aa8510560ab41");
+    assert_eq!(request["variables"]["first"], 10);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_graphql_errors_are_surfaced() -> Result<()> {
+    let (endpoint, _) = stub("/subgraph", |_| {
+        r#"{"errors":[{"message":"Type `Query` has no field `totals`"}]}"#.to_string()
+    });
+    let client = SubgraphClient::with_endpoint(&endpoint)?;
+
+    let error = client.totals().await.unwrap_err();
+
+    assert!(error.to_string().contains("has no field"));
+    Ok(())
+}
+
+/// Requires a The Graph API key in `THE_GRAPH_API_KEY`.
+#[tokio::test]
+#[ignore]
+async fn test_daily_totals_on_mainnet() -> Result<()> {
+    let client = SubgraphClient::new(Network::Mainnet, &std::env::var("THE_GRAPH_API_KEY")?)?;
+
+    let days = client.daily_totals(7).await?;
+
+    assert_eq!(days.len(), 7);
+    assert!(days.windows(2).all(|pair| pair[0].timestamp > pair[1].timestamp));
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use alloy::{
+    primitives::{U256, address},
+    providers::ProviderBuilder,
+};
+use cow_sdk::{config::network::Network, tokens::TokenRegistry};
+use eyre::Result;
+
+/// Requires a mainnet RPC URL in `ETH_RPC_URL`.
+#[tokio::test]
+#[ignore]
+async fn test_resolve_token_metadata_on_mainnet() -> Result<()> {
+    let provider = ProviderBuilder::new().on_http(std::env::var("ETH_RPC_URL")?.parse()?);
+    let registry = TokenRegistry::new(provider, Network::Mainnet);
+    let usdc = address!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+
+    let token = registry.token(usdc).await?;
+
+    assert_eq!(token.symbol, "USDC");
+    assert_eq!(token.decimals, 6);
+    assert_eq!(registry.format_amount(usdc, U256::from(2_500_000)).await?, "2.5 USDC");
+
+    Ok(())
+}
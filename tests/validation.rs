@@ -0,0 +1,192 @@
+use alloy::{
+    network::EthereumWallet,
+    node_bindings::Anvil,
+    primitives::{Address, B256, Bytes, U256, address, bytes},
+    providers::{Provider, ProviderBuilder},
+    signers::local::PrivateKeySigner,
+    sol_types::SolCall,
+};
+use cow_sdk::{
+    approval::{ApprovalAmount, TokenApprovals},
+    config::network::Network,
+    contracts::IERC20,
+    primitives::order_data::{OrderData, OrderKind},
+    validation::{OrderProblem, OrderValidator},
+};
+use eyre::Result;
+
+const WETH: Address = address!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+const USDC: Address = address!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+
+/// Runtime code of a mock token answering every call with the word stored at
+/// the slot of the call's selector, e.g. `balanceOf` reads slot `0x70a08231`.
+const MOCK_TOKEN_CODE: Bytes = bytes!("60003560e01c5460005260206000f3");
+
+/// Deploys a mock token whose `balanceOf` and `allowance` always return
+/// `balance` and `allowance`.
+async fn deploy_mock_token(
+    provider: &impl Provider,
+    token: Address,
+    balance: U256,
+    allowance: U256,
+) -> Result<()> {
+    provider.raw_request::<_, ()>("anvil_setCode".into(), (token, MOCK_TOKEN_CODE)).await?;
+    for (selector, value) in [(0x70a08231u32, balance), (0xdd62ed3e, allowance)] {
+        provider
+            .raw_request::<_, ()>(
+                "anvil_setStorageAt".into(),
+                (token, U256::from(selector), B256::from(value)),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+/// Requires `anvil`.
+#[tokio::test]
+async fn test_validate_order_against_mock_token() -> Result<()> {
+    let anvil = Anvil::new().try_spawn()?;
+    let provider = ProviderBuilder::new().on_http(anvil.endpoint_url());
+    let token = Address::repeat_byte(0x01);
+    deploy_mock_token(&provider, token, U256::from(1_000), U256::from(500)).await?;
+    let validator = OrderValidator::new(&provider, Network::Mainnet);
+    let order = |sell_amount: u64, fee_amount: u64| OrderData {
+        sell_token: token,
+        buy_token: Address::repeat_byte(0x02),
+        sell_amount: U256::from(sell_amount),
+        buy_amount: U256::from(1),
+        fee_amount: U256::from(fee_amount),
+        valid_to: u32::MAX,
+        kind: OrderKind::Sell,
+        ..Default::default()
+    };
+    let owner = Address::repeat_byte(0x42);
+
+    assert!(validator.validate(&order(500, 0), owner).await?.is_empty());
+    assert_eq!(
+        validator.validate(&order(900, 200), owner).await?,
+        vec![
+            OrderProblem::InsufficientBalance {
+                balance: U256::from(1_000),
+                required: U256::from(1_100)
+            },
+            OrderProblem::InsufficientAllowance {
+                allowance: U256::from(500),
+                required: U256::from(1_100)
+            },
+        ]
+    );
+    let overflowing = OrderData { sell_amount: U256::MAX, ..order(0, 1) };
+    assert_eq!(validator.validate(&overflowing, owner).await?, vec![OrderProblem::AmountOverflow]);
+
+    Ok(())
+}
+
+/// Requires `anvil`.
+#[tokio::test]
+async fn test_missing_approval_of_overflowing_order() -> Result<()> {
+    let anvil = Anvil::new().try_spawn()?;
+    let provider = ProviderBuilder::new().on_http(anvil.endpoint_url());
+    let token = Address::repeat_byte(0x01);
+    deploy_mock_token(&provider, token, U256::ZERO, U256::from(500)).await?;
+    let approvals = TokenApprovals::new(&provider, Network::Mainnet);
+    let order = OrderData {
+        sell_token: token,
+        buy_token: Address::repeat_byte(0x02),
+        sell_amount: U256::MAX,
+        buy_amount: U256::from(1),
+        fee_amount: U256::from(1),
+        ..Default::default()
+    };
+
+    let transactions = approvals.missing_approvals(&order, Address::repeat_byte(0x42)).await?;
+
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].to, Some(token.into()));
+    let call = IERC20::approveCall::abi_decode(transactions[0].input.input().unwrap(), true)?;
+    assert_eq!(call.amount, U256::MAX);
+    Ok(())
+}
+
+/// Requires `anvil` and a mainnet RPC URL in `ETH_RPC_URL`.
+#[tokio::test]
+#[ignore]
+async fn test_validate_order_without_balance_on_mainnet_fork() -> Result<()> {
+    let anvil = Anvil::new().fork(std::env::var("ETH_RPC_URL")?).try_spawn()?;
+    let provider = ProviderBuilder::new().on_http(anvil.endpoint_url());
+    let validator = OrderValidator::new(provider, Network::Mainnet);
+    let order = OrderData {
+        sell_token: WETH,
+        buy_token: USDC,
+        sell_amount: U256::from(10).pow(U256::from(18)),
+        buy_amount: U256::from(1_000_000),
+        valid_to: u32::MAX,
+        kind: OrderKind::Sell,
+        ..Default::default()
+    };
+
+    let problems = validator.validate(&order, Address::repeat_byte(0x42)).await?;
+
+    assert_eq!(
+        problems,
+        vec![
+            OrderProblem::InsufficientBalance { balance: U256::ZERO, required: order.sell_amount },
+            OrderProblem::InsufficientAllowance {
+                allowance: U256::ZERO,
+                required: order.sell_amount
+            },
+        ]
+    );
+
+    Ok(())
+}
+
+/// Requires `anvil` and a mainnet RPC URL in `ETH_RPC_URL`.
+#[tokio::test]
+#[ignore]
+async fn test_validate_expired_order_on_mainnet_fork() -> Result<()> {
+    let anvil = Anvil::new().fork(std::env::var("ETH_RPC_URL")?).try_spawn()?;
+    let provider = ProviderBuilder::new().on_http(anvil.endpoint_url());
+    let validator = OrderValidator::new(provider, Network::Mainnet);
+    let order = OrderData {
+        sell_token: WETH,
+        buy_token: USDC,
+        sell_amount: U256::from(1),
+        buy_amount: U256::from(1),
+        valid_to: 1,
+        partially_fillable: true,
+        ..Default::default()
+    };
+
+    let problems = validator.validate(&order, Address::repeat_byte(0x42)).await?;
+
+    assert!(problems.iter().any(|problem| matches!(problem, OrderProblem::Expired { .. })));
+
+    Ok(())
+}
+
+/// Requires `anvil` and a mainnet RPC URL in `ETH_RPC_URL`.
+#[tokio::test]
+#[ignore]
+async fn test_approval_fixes_allowance_problem_on_mainnet_fork() -> Result<()> {
+    let anvil = Anvil::new().fork(std::env::var("ETH_RPC_URL")?).try_spawn()?;
+    let signer: PrivateKeySigner = anvil.keys()[0].clone().into();
+    let owner = signer.address();
+    let provider =
+        ProviderBuilder::new().wallet(EthereumWallet::from(signer)).on_http(anvil.endpoint_url());
+    let approvals = TokenApprovals::new(&provider, Network::Mainnet);
+
+    approvals.approve(WETH, ApprovalAmount::Unlimited).await?;
+
+    assert_eq!(approvals.allowance(WETH, owner).await?, U256::MAX);
+    let order = OrderData {
+        sell_token: WETH,
+        buy_token: USDC,
+        sell_amount: U256::from(1),
+        buy_amount: U256::from(1),
+        ..Default::default()
+    };
+    assert!(approvals.missing_approvals(&order, owner).await?.is_empty());
+
+    Ok(())
+}